@@ -1,11 +1,31 @@
 use std::hint::black_box;
+use std::iter;
+use std::ops::Range;
 
 use capnp::message::{Builder, ReaderOptions};
 use codec_comparison::{
-    block_capnp, generate_test_data, manual_zerocopy, manual_zerocopy_v2, manual_zerocopy_v3,
-    ArchivedBlock, Block, FullTerm,
+    block_capnp, block_fbs, block_flat_capnp, block_proto,
+    block_summary::{self, BlockSummary},
+    capnp_view,
+    compression::Compression,
+    generate_postings, generate_test_data, generate_test_data_with_clustered_masks,
+    generate_test_data_with_distinct_masks,
+    live_docs::LiveDocs,
+    manual_bitpacked, manual_dictionary, manual_elias_fano, manual_roaring, manual_zerocopy,
+    manual_zerocopy_v2, manual_zerocopy_v3, manual_zerocopy_v4,
+    merge::{self, Segment},
+    postings::{self, Postings},
+    predicate::Predicate,
+    top_k::{self, TopK},
+    ArchivedBlock, Block, FullTerm, Xorshift64,
 };
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, Criterion, Throughput,
+};
+use flatbuffers::FlatBufferBuilder;
+use prost::Message;
+use speedy::{Readable, Writable};
 
 fn print_size_stats(name: &str, total_size: usize) {
     println!(
@@ -24,6 +44,20 @@ fn print_size_stats(name: &str, total_size: usize) {
     );
 }
 
+fn print_overhead_stats(baseline: &str, baseline_size: usize, total_size: usize) {
+    let overhead = total_size as i64 - baseline_size as i64;
+    println!(
+        "  Overhead vs {}: {} bytes ({:.2}%, {:.2} bytes per block)",
+        baseline,
+        overhead,
+        overhead as f64 / baseline_size as f64 * 100.0,
+        overhead as f64 / 10_000.0
+    );
+}
+
+// CRC used for the postcard CRC32 flavor
+const POSTCARD_CRC: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISCSI);
+
 fn measure_sizes() {
     let test_data = generate_test_data();
 
@@ -46,6 +80,24 @@ fn measure_sizes() {
     }
     print_size_stats("bincode", bincode_size);
 
+    // Measure bincode fixed-int size
+    let bincode_fixed_int_config = bincode::config::standard().with_fixed_int_encoding();
+    let mut bincode_fixed_int_size = 0;
+    for block in &test_data {
+        let bytes = bincode::encode_to_vec(block, bincode_fixed_int_config).unwrap();
+        bincode_fixed_int_size += bytes.len();
+    }
+    print_size_stats("bincode_fixed_int", bincode_fixed_int_size);
+
+    // Measure bincode big-endian size
+    let bincode_big_endian_config = bincode::config::standard().with_big_endian();
+    let mut bincode_big_endian_size = 0;
+    for block in &test_data {
+        let bytes = bincode::encode_to_vec(block, bincode_big_endian_config).unwrap();
+        bincode_big_endian_size += bytes.len();
+    }
+    print_size_stats("bincode_big_endian", bincode_big_endian_size);
+
     // Measure postcard size
     let mut postcard_size = 0;
     for block in &test_data {
@@ -54,16 +106,137 @@ fn measure_sizes() {
     }
     print_size_stats("postcard", postcard_size);
 
+    // Measure postcard COBS-framed size
+    let mut postcard_cobs_size = 0;
+    for block in &test_data {
+        let bytes = postcard::to_stdvec_cobs(block).unwrap();
+        postcard_cobs_size += bytes.len();
+    }
+    print_size_stats("postcard_cobs", postcard_cobs_size);
+    print_overhead_stats("postcard", postcard_size, postcard_cobs_size);
+
+    // Measure postcard CRC32 size
+    let mut postcard_crc32_size = 0;
+    for block in &test_data {
+        let bytes = postcard::to_stdvec_crc32(block, POSTCARD_CRC.digest()).unwrap();
+        postcard_crc32_size += bytes.len();
+    }
+    print_size_stats("postcard_crc32", postcard_crc32_size);
+    print_overhead_stats("postcard", postcard_size, postcard_crc32_size);
+
+    // Measure MessagePack size
+    let mut messagepack_size = 0;
+    for block in &test_data {
+        let bytes = rmp_serde::to_vec(block).unwrap();
+        messagepack_size += bytes.len();
+    }
+    print_size_stats("messagepack", messagepack_size);
+
+    // Measure CBOR size
+    let mut cbor_size = 0;
+    for block in &test_data {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(block, &mut bytes).unwrap();
+        cbor_size += bytes.len();
+    }
+    print_size_stats("cbor", cbor_size);
+
+    // Measure bitcode size
+    let mut bitcode_size = 0;
+    for block in &test_data {
+        let bytes = bitcode::encode(block);
+        bitcode_size += bytes.len();
+    }
+    print_size_stats("bitcode", bitcode_size);
+
+    // Measure borsh size
+    let mut borsh_size = 0;
+    for block in &test_data {
+        let bytes = borsh::to_vec(block).unwrap();
+        borsh_size += bytes.len();
+    }
+    print_size_stats("borsh", borsh_size);
+
+    // Measure speedy size
+    let mut speedy_size = 0;
+    for block in &test_data {
+        let bytes = block.write_to_vec().unwrap();
+        speedy_size += bytes.len();
+    }
+    print_size_stats("speedy", speedy_size);
+
     // Measure capnp size
     let mut capnp_size = 0;
     for block in &test_data {
         let mut message = Builder::new_default();
         block.to_capnp(&mut message);
         let bytes = capnp::serialize::write_message_to_words(&message);
-        capnp_size += bytes.len() * 8; // words are 8 bytes each
+        capnp_size += bytes.len();
     }
     print_size_stats("capnp", capnp_size);
 
+    // Measure capnp packed size
+    let mut capnp_packed_size = 0;
+    for block in &test_data {
+        let mut message = Builder::new_default();
+        block.to_capnp(&mut message);
+        let mut bytes = Vec::new();
+        capnp::serialize_packed::write_message(&mut bytes, &message).unwrap();
+        capnp_packed_size += bytes.len();
+    }
+    print_size_stats("capnp_packed", capnp_packed_size);
+
+    // Measure capnp flat schema size
+    let mut capnp_flat_size = 0;
+    for block in &test_data {
+        let mut message = Builder::new_default();
+        block.to_capnp_flat(&mut message);
+        let bytes = capnp::serialize::write_message_to_words(&message);
+        capnp_flat_size += bytes.len();
+    }
+    print_size_stats("capnp_flat", capnp_flat_size);
+
+    // Measure flatbuffers size
+    let mut flatbuffers_size = 0;
+    for block in &test_data {
+        let mut builder = FlatBufferBuilder::new();
+        block.to_flatbuffers(&mut builder);
+        flatbuffers_size += builder.finished_data().len();
+    }
+    print_size_stats("flatbuffers", flatbuffers_size);
+
+    // Measure protobuf size
+    let mut protobuf_size = 0;
+    for block in &test_data {
+        let bytes = block.to_proto().encode_to_vec();
+        protobuf_size += bytes.len();
+    }
+    print_size_stats("protobuf", protobuf_size);
+
+    // Measure protobuf fixed64 size
+    let mut protobuf_fixed_size = 0;
+    for block in &test_data {
+        let bytes = block.to_proto_fixed().encode_to_vec();
+        protobuf_fixed_size += bytes.len();
+    }
+    print_size_stats("protobuf_fixed", protobuf_fixed_size);
+
+    // Measure protobuf packed size
+    let mut protobuf_packed_size = 0;
+    for block in &test_data {
+        let bytes = block.to_proto_packed().encode_to_vec();
+        protobuf_packed_size += bytes.len();
+    }
+    print_size_stats("protobuf_packed", protobuf_packed_size);
+
+    // Measure protobuf packed fixed64 size
+    let mut protobuf_packed_fixed_size = 0;
+    for block in &test_data {
+        let bytes = block.to_proto_packed_fixed().encode_to_vec();
+        protobuf_packed_fixed_size += bytes.len();
+    }
+    print_size_stats("protobuf_packed_fixed", protobuf_packed_fixed_size);
+
     // Measure manual zero-copy size
     let mut manual_size = 0;
     for block in &test_data {
@@ -88,6 +261,235 @@ fn measure_sizes() {
     }
     print_size_stats("manual_zerocopy_v3", manual_v3_size);
 
+    // Measure manual zero-copy v4 size
+    let mut manual_v4_size = 0;
+    for block in &test_data {
+        let bytes = manual_zerocopy_v4::serialize(block);
+        manual_v4_size += bytes.len();
+    }
+    print_size_stats("manual_zerocopy_v4", manual_v4_size);
+
+    // Measure manual bit-packed size
+    let mut manual_bitpacked_size = 0;
+    for block in &test_data {
+        let bytes = manual_bitpacked::serialize(block);
+        manual_bitpacked_size += bytes.len();
+    }
+    print_size_stats("manual_bitpacked", manual_bitpacked_size);
+
+    // Measure manual Elias-Fano size
+    let mut manual_elias_fano_size = 0;
+    for block in &test_data {
+        let bytes = manual_elias_fano::serialize(block).unwrap();
+        manual_elias_fano_size += bytes.len();
+    }
+    print_size_stats("manual_elias_fano", manual_elias_fano_size);
+
+    // Measure manual dictionary size
+    let mut manual_dictionary_size = 0;
+    for block in &test_data {
+        let bytes = manual_dictionary::serialize(block);
+        manual_dictionary_size += bytes.len();
+    }
+    print_size_stats("manual_dictionary", manual_dictionary_size);
+
+    // Measure manual roaring size
+    let mut manual_roaring_size = 0;
+    for block in &test_data {
+        let bytes = manual_roaring::serialize(block);
+        manual_roaring_size += bytes.len();
+    }
+    print_size_stats("manual_roaring", manual_roaring_size);
+
+    println!(); // Extra newline after all sizes
+}
+
+// Compression settings layered over the codecs in the compressed benchmarks
+const COMPRESSIONS: [Compression; 4] = [
+    Compression::Lz4 { level: 0 },
+    Compression::Lz4 { level: 9 },
+    Compression::Zstd { level: 1 },
+    Compression::Zstd { level: 9 },
+];
+
+// Codecs that the compressed read benchmarks decode, in the order
+// serialize_for_compression lists them
+const COMPRESSED_READ_CODECS: [&str; 6] = [
+    "rkyv",
+    "bincode",
+    "postcard",
+    "capnp",
+    "flatbuffers",
+    "manual_zerocopy",
+];
+
+// Serialize every block with each codec that takes part in the compressed benchmarks:
+// the read codecs first, then the ones that only report compressed sizes
+fn serialize_for_compression(test_data: &[Block]) -> Vec<(&'static str, Vec<Vec<u8>>)> {
+    let bincode_config = bincode::config::standard();
+    let bincode_fixed_int_config = bincode::config::standard().with_fixed_int_encoding();
+    let bincode_big_endian_config = bincode::config::standard().with_big_endian();
+
+    vec![
+        (
+            "rkyv",
+            test_data
+                .iter()
+                .map(|block| {
+                    rkyv::to_bytes::<rkyv::rancor::Error>(block)
+                        .unwrap()
+                        .to_vec()
+                })
+                .collect(),
+        ),
+        (
+            "bincode",
+            test_data
+                .iter()
+                .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
+                .collect(),
+        ),
+        (
+            "postcard",
+            test_data
+                .iter()
+                .map(|block| postcard::to_stdvec(block).unwrap())
+                .collect(),
+        ),
+        (
+            "capnp",
+            test_data
+                .iter()
+                .map(|block| {
+                    let mut message = Builder::new_default();
+                    block.to_capnp(&mut message);
+                    capnp::serialize::write_message_to_words(&message)
+                })
+                .collect(),
+        ),
+        (
+            "flatbuffers",
+            test_data
+                .iter()
+                .map(|block| {
+                    let mut builder = FlatBufferBuilder::new();
+                    block.to_flatbuffers(&mut builder);
+                    builder.finished_data().to_vec()
+                })
+                .collect(),
+        ),
+        (
+            "manual_zerocopy",
+            test_data.iter().map(manual_zerocopy::serialize).collect(),
+        ),
+        (
+            "bincode_fixed_int",
+            test_data
+                .iter()
+                .map(|block| bincode::encode_to_vec(block, bincode_fixed_int_config).unwrap())
+                .collect(),
+        ),
+        (
+            "bincode_big_endian",
+            test_data
+                .iter()
+                .map(|block| bincode::encode_to_vec(block, bincode_big_endian_config).unwrap())
+                .collect(),
+        ),
+        (
+            "messagepack",
+            test_data
+                .iter()
+                .map(|block| rmp_serde::to_vec(block).unwrap())
+                .collect(),
+        ),
+        (
+            "cbor",
+            test_data
+                .iter()
+                .map(|block| {
+                    let mut bytes = Vec::new();
+                    ciborium::into_writer(block, &mut bytes).unwrap();
+                    bytes
+                })
+                .collect(),
+        ),
+        ("bitcode", test_data.iter().map(bitcode::encode).collect()),
+        (
+            "borsh",
+            test_data
+                .iter()
+                .map(|block| borsh::to_vec(block).unwrap())
+                .collect(),
+        ),
+        (
+            "speedy",
+            test_data
+                .iter()
+                .map(|block| block.write_to_vec().unwrap())
+                .collect(),
+        ),
+        (
+            "protobuf",
+            test_data
+                .iter()
+                .map(|block| block.to_proto().encode_to_vec())
+                .collect(),
+        ),
+        (
+            "manual_bitpacked",
+            test_data.iter().map(manual_bitpacked::serialize).collect(),
+        ),
+        (
+            "manual_elias_fano",
+            test_data
+                .iter()
+                .map(|block| manual_elias_fano::serialize(block).unwrap())
+                .collect(),
+        ),
+        (
+            "manual_dictionary",
+            test_data.iter().map(manual_dictionary::serialize).collect(),
+        ),
+        (
+            "manual_roaring",
+            test_data.iter().map(manual_roaring::serialize).collect(),
+        ),
+    ]
+}
+
+fn compress_blocks(blocks: &[Vec<u8>], compression: Compression) -> Vec<Vec<u8>> {
+    blocks
+        .iter()
+        .map(|block| compression.compress(block).unwrap())
+        .collect()
+}
+
+fn measure_compressed_sizes() {
+    let test_data = generate_test_data();
+
+    println!("\n=== Compressed Encoding Sizes ===");
+
+    for (codec, blocks) in serialize_for_compression(&test_data) {
+        let uncompressed_size: usize = blocks.iter().map(Vec::len).sum();
+
+        for compression in COMPRESSIONS {
+            let compressed_size: usize = compress_blocks(&blocks, compression)
+                .iter()
+                .map(Vec::len)
+                .sum();
+
+            print_size_stats(
+                &format!("{}+{}", codec, compression.name()),
+                compressed_size,
+            );
+            println!(
+                "  Compression ratio: {:.2}x",
+                uncompressed_size as f64 / compressed_size as f64
+            );
+        }
+    }
+
     println!(); // Extra newline after all sizes
 }
 
@@ -104,6 +506,83 @@ fn create_query_mask(target_rate: f64) -> u128 {
     }
 }
 
+// Helper function to report how many blocks a summary header lets a filter skip
+fn print_skip_rate(label: &str, test_data: &[Block], query_mask: u128) {
+    let skipped = test_data
+        .iter()
+        .filter(|block| !BlockSummary::new(block).may_match(query_mask))
+        .count();
+
+    println!(
+        "{label}: summary skips {skipped} of {} blocks ({:.1}%)",
+        test_data.len(),
+        skipped as f64 / test_data.len() as f64 * 100.0
+    );
+}
+
+// Share of terms deleted in the full and filtered read benchmarks, taken from
+// the DELETION_RATIO environment variable (0.0 to 1.0, default 0.0)
+fn deletion_ratio() -> f64 {
+    std::env::var("DELETION_RATIO")
+        .ok()
+        .and_then(|ratio| ratio.parse::<f64>().ok())
+        .map_or(0.0, |ratio| ratio.clamp(0.0, 1.0))
+}
+
+// Helper function to delete ~ratio of the terms of every block at random
+fn generate_live_docs(test_data: &[Block], ratio: f64) -> Vec<LiveDocs> {
+    let mut rng = Xorshift64::new(13);
+
+    test_data
+        .iter()
+        .map(|block| {
+            let mut live_docs = LiveDocs::new(block.full_terms.len());
+            for position in 0..block.full_terms.len() {
+                if (rng.next() as f64 / u64::MAX as f64) < ratio {
+                    live_docs.delete(position);
+                }
+            }
+            live_docs
+        })
+        .collect()
+}
+
+// Each block's live docs, or `None` for every block when nothing was deleted
+fn each_block_live_docs(
+    live_docs: &Option<Vec<LiveDocs>>,
+) -> impl Iterator<Item = Option<&LiveDocs>> + '_ {
+    live_docs
+        .iter()
+        .flatten()
+        .map(Some)
+        .chain(iter::repeat(None))
+}
+
+// Loop over a block's terms, dropping the ones deleted in its live docs.
+// Without live docs this is the plain loop, so runs without deletions measure
+// the same code as before live docs existed.
+macro_rules! for_live {
+    ($pattern:pat in $terms:expr, $live_docs:expr, $body:block) => {
+        match $live_docs {
+            Some(live_docs) => {
+                for $pattern in live_docs.filter($terms) $body
+            }
+            None => {
+                for $pattern in $terms $body
+            }
+        }
+    };
+}
+
+// Helper function to tag a group name with the deletion ratio, if any
+fn with_deletion_suffix(group_name: &str, ratio: f64) -> String {
+    if ratio > 0.0 {
+        format!("{group_name}_deleted_{}%", (ratio * 100.0).round() as u32)
+    } else {
+        group_name.to_string()
+    }
+}
+
 fn benchmark_serialize(c: &mut Criterion) {
     let test_data = generate_test_data();
     let bincode_config = bincode::config::standard();
@@ -137,185 +616,3382 @@ fn benchmark_serialize(c: &mut Criterion) {
         });
     });
 
-    group.bench_function("capnp", |b| {
+    group.bench_function("postcard_cobs", |b| {
         b.iter(|| {
             for block in black_box(&test_data) {
-                let mut message = Builder::new_default();
-                block.to_capnp(&mut message);
-                let bytes = capnp::serialize::write_message_to_words(&message);
+                let bytes = postcard::to_stdvec_cobs(block).unwrap();
                 black_box(bytes);
             }
         });
     });
 
-    group.bench_function("manual_zerocopy", |b| {
+    group.bench_function("postcard_crc32", |b| {
         b.iter(|| {
             for block in black_box(&test_data) {
-                let bytes = manual_zerocopy::serialize(block);
+                let bytes = postcard::to_stdvec_crc32(block, POSTCARD_CRC.digest()).unwrap();
                 black_box(bytes);
             }
         });
     });
 
-    group.bench_function("manual_zerocopy_v2", |b| {
+    group.bench_function("messagepack", |b| {
         b.iter(|| {
             for block in black_box(&test_data) {
-                let bytes = manual_zerocopy_v2::serialize(block);
+                let bytes = rmp_serde::to_vec(block).unwrap();
                 black_box(bytes);
             }
         });
     });
 
-    group.bench_function("manual_zerocopy_v3", |b| {
+    group.bench_function("cbor", |b| {
         b.iter(|| {
             for block in black_box(&test_data) {
-                let bytes = manual_zerocopy_v3::serialize(block);
+                let mut bytes = Vec::new();
+                ciborium::into_writer(block, &mut bytes).unwrap();
                 black_box(bytes);
             }
         });
     });
 
-    group.finish();
-}
-
-fn benchmark_full_read(c: &mut Criterion) {
-    let test_data = generate_test_data();
-    let bincode_config = bincode::config::standard();
-
-    // Pre-serialize data for all formats
-    let rkyv_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
-        .collect();
-
-    let bincode_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
-        .collect();
-
-    let postcard_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| postcard::to_stdvec(block).unwrap())
-        .collect();
-
-    let capnp_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| {
-            let mut message = Builder::new_default();
-            block.to_capnp(&mut message);
-            capnp::serialize::write_message_to_words(&message)
-        })
-        .collect();
-
-    let manual_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| manual_zerocopy::serialize(block))
-        .collect();
-
-    let manual_v2_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| manual_zerocopy_v2::serialize(block))
-        .collect();
-
-    let manual_v3_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| manual_zerocopy_v3::serialize(block))
-        .collect();
-
-    let mut group = c.benchmark_group("full_read");
-
-    group.bench_function("rkyv", |b| {
+    group.bench_function("bitcode", |b| {
         b.iter(|| {
-            let mut total_frequency = 0u64;
-
-            for serialized_block in black_box(&rkyv_blocks) {
-                let block =
-                    rkyv::from_bytes::<Block, rkyv::rancor::Error>(serialized_block).unwrap();
-
-                for term in &block.full_terms {
-                    let _doc_id = term.doc_id;
-                    let _field_mask = term.field_mask;
-                    total_frequency += term.frequency;
-                }
+            for block in black_box(&test_data) {
+                let bytes = bitcode::encode(block);
+                black_box(bytes);
             }
-
-            total_frequency
         });
     });
 
-    group.bench_function("bincode", |b| {
+    group.bench_function("borsh", |b| {
         b.iter(|| {
-            let mut total_frequency = 0u64;
-
-            for serialized_block in black_box(&bincode_blocks) {
-                let (block, _len): (Block, usize) =
-                    bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
-
-                for term in &block.full_terms {
-                    let _doc_id = term.doc_id;
-                    let _field_mask = term.field_mask;
-                    total_frequency += term.frequency;
-                }
+            for block in black_box(&test_data) {
+                let bytes = borsh::to_vec(block).unwrap();
+                black_box(bytes);
             }
-
-            total_frequency
         });
     });
 
-    group.bench_function("postcard", |b| {
+    group.bench_function("speedy", |b| {
         b.iter(|| {
-            let mut total_frequency = 0u64;
-
-            for serialized_block in black_box(&postcard_blocks) {
-                let block: Block = postcard::from_bytes(serialized_block).unwrap();
-
-                for term in &block.full_terms {
-                    let _doc_id = term.doc_id;
-                    let _field_mask = term.field_mask;
-                    total_frequency += term.frequency;
-                }
+            for block in black_box(&test_data) {
+                let bytes = block.write_to_vec().unwrap();
+                black_box(bytes);
             }
-
-            total_frequency
         });
     });
 
     group.bench_function("capnp", |b| {
         b.iter(|| {
-            let mut total_frequency = 0u64;
-
-            for serialized_block in black_box(&capnp_blocks) {
-                let reader = capnp::serialize::read_message_from_flat_slice(
-                    &mut &serialized_block[..],
-                    ReaderOptions::new(),
-                )
-                .unwrap();
-                let block =
-                    Block::from_capnp(reader.get_root::<block_capnp::block::Reader>().unwrap())
-                        .unwrap();
-
-                for term in &block.full_terms {
-                    let _doc_id = term.doc_id;
-                    let _field_mask = term.field_mask;
-                    total_frequency += term.frequency;
-                }
+            for block in black_box(&test_data) {
+                let mut message = Builder::new_default();
+                block.to_capnp(&mut message);
+                let bytes = capnp::serialize::write_message_to_words(&message);
+                black_box(bytes);
             }
-
-            total_frequency
         });
     });
 
-    group.bench_function("manual_zerocopy", |b| {
+    group.bench_function("capnp_packed", |b| {
         b.iter(|| {
-            let mut total_frequency = 0u64;
-
-            for serialized_block in black_box(&manual_blocks) {
-                let block = manual_zerocopy::deserialize(serialized_block).unwrap();
+            for block in black_box(&test_data) {
+                let mut message = Builder::new_default();
+                block.to_capnp(&mut message);
+                let mut bytes = Vec::new();
+                capnp::serialize_packed::write_message(&mut bytes, &message).unwrap();
+                black_box(bytes);
+            }
+        });
+    });
 
-                for term in &block.full_terms {
+    group.bench_function("capnp_flat", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let mut message = Builder::new_default();
+                block.to_capnp_flat(&mut message);
+                let bytes = capnp::serialize::write_message_to_words(&message);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("flatbuffers", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let mut builder = FlatBufferBuilder::new();
+                block.to_flatbuffers(&mut builder);
+                black_box(builder.finished_data());
+            }
+        });
+    });
+
+    group.bench_function("protobuf", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = block.to_proto().encode_to_vec();
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("protobuf_fixed", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = block.to_proto_fixed().encode_to_vec();
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("protobuf_packed", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = block.to_proto_packed().encode_to_vec();
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("protobuf_packed_fixed", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = block.to_proto_packed_fixed().encode_to_vec();
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_zerocopy::serialize(block);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy_v2", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_zerocopy_v2::serialize(block);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy_v3", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_zerocopy_v3::serialize(block);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy_v4", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_zerocopy_v4::serialize(block);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_bitpacked", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_bitpacked::serialize(block);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_elias_fano", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_elias_fano::serialize(block).unwrap();
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.bench_function("manual_roaring", |b| {
+        b.iter(|| {
+            for block in black_box(&test_data) {
+                let bytes = manual_roaring::serialize(block);
+                black_box(bytes);
+            }
+        });
+    });
+
+    group.finish();
+}
+
+fn benchmark_streaming_write(c: &mut Criterion) {
+    let test_data = generate_test_data();
+
+    let mut group = c.benchmark_group("streaming_write");
+
+    // Terms arrive one at a time, as from an indexer: the materialized variants
+    // collect them into a Block first, the writers stream them into the output
+    group.bench_function("manual_zerocopy_materialized", |b| {
+        let mut bytes = Vec::new();
+
+        b.iter(|| {
+            bytes.clear();
+
+            for block in black_box(&test_data) {
+                let mut full_terms = Vec::new();
+                for term in &block.full_terms {
+                    full_terms.push(term.clone());
+                }
+                let block = Block { full_terms };
+                bytes.extend_from_slice(&manual_zerocopy::serialize(&block));
+            }
+
+            bytes.len()
+        });
+    });
+
+    group.bench_function("manual_zerocopy_writer", |b| {
+        let mut bytes = Vec::new();
+
+        b.iter(|| {
+            bytes.clear();
+
+            for block in black_box(&test_data) {
+                let mut writer = manual_zerocopy::BlockWriter::new(&mut bytes);
+                for term in &block.full_terms {
+                    writer.push(term).unwrap();
+                }
+                writer.finish();
+            }
+
+            bytes.len()
+        });
+    });
+
+    group.bench_function("capnp_materialized", |b| {
+        let mut bytes = Vec::new();
+
+        b.iter(|| {
+            bytes.clear();
+
+            for block in black_box(&test_data) {
+                let mut full_terms = Vec::new();
+                for term in &block.full_terms {
+                    full_terms.push(term.clone());
+                }
+                let block = Block { full_terms };
+                let mut message = Builder::new_default();
+                block.to_capnp(&mut message);
+                capnp::serialize::write_message(&mut bytes, &message).unwrap();
+            }
+
+            bytes.len()
+        });
+    });
+
+    group.bench_function("capnp_writer", |b| {
+        let mut bytes = Vec::new();
+
+        b.iter(|| {
+            bytes.clear();
+
+            for block in black_box(&test_data) {
+                let mut message = Builder::new_default();
+                let mut writer = capnp_view::BlockWriter::new(&mut message);
+                for term in &block.full_terms {
+                    writer.push(term).unwrap();
+                }
+                writer.finish().unwrap();
+                capnp::serialize::write_message(&mut bytes, &message).unwrap();
+            }
+
+            bytes.len()
+        });
+    });
+
+    group.finish();
+}
+
+fn benchmark_in_place_update(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let bincode_config = bincode::config::standard();
+
+    let manual_blocks: Vec<_> = test_data.iter().map(manual_zerocopy::serialize).collect();
+    let bincode_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
+        .collect();
+
+    // Touch every 10th term of every block
+    let mut group = c.benchmark_group("in_place_update");
+
+    group.bench_function("manual_zerocopy_in_place", |b| {
+        let mut blocks = manual_blocks.clone();
+
+        b.iter(|| {
+            for serialized_block in black_box(&mut blocks) {
+                let mut writer = manual_zerocopy::BlockWriterMut::new(serialized_block).unwrap();
+
+                for mut term in writer.iter_mut().step_by(10) {
+                    let frequency = term.frequency();
+                    term.set_frequency(frequency % 1000 + 1).unwrap();
+                }
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy_reencode", |b| {
+        let mut blocks = manual_blocks.clone();
+
+        b.iter(|| {
+            for serialized_block in black_box(&mut blocks) {
+                let mut block = manual_zerocopy::deserialize(serialized_block).unwrap();
+
+                for term in block.full_terms.iter_mut().step_by(10) {
+                    term.frequency = term.frequency % 1000 + 1;
+                }
+
+                *serialized_block = manual_zerocopy::serialize(&block);
+            }
+        });
+    });
+
+    group.bench_function("bincode_reencode", |b| {
+        let mut blocks = bincode_blocks.clone();
+
+        b.iter(|| {
+            for serialized_block in black_box(&mut blocks) {
+                let (mut block, _len): (Block, usize) =
+                    bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
+
+                for term in block.full_terms.iter_mut().step_by(10) {
+                    term.frequency = term.frequency % 1000 + 1;
+                }
+
+                *serialized_block = bincode::encode_to_vec(&block, bincode_config).unwrap();
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy_tombstone", |b| {
+        let mut blocks = manual_blocks.clone();
+
+        b.iter(|| {
+            for serialized_block in black_box(&mut blocks) {
+                let mut writer = manual_zerocopy::BlockWriterMut::new(serialized_block).unwrap();
+
+                for mut term in writer.iter_mut().step_by(10) {
+                    term.tombstone();
+                }
+            }
+        });
+    });
+
+    group.bench_function("manual_zerocopy_delete_reencode", |b| {
+        b.iter_batched(
+            || manual_blocks.clone(),
+            |mut blocks| {
+                for serialized_block in &mut blocks {
+                    let mut block = manual_zerocopy::deserialize(serialized_block).unwrap();
+
+                    let mut index = 0;
+                    block.full_terms.retain(|_| {
+                        index += 1;
+                        (index - 1) % 10 != 0
+                    });
+
+                    *serialized_block = manual_zerocopy::serialize(&block);
+                }
+
+                blocks
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
+fn benchmark_full_read(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let bincode_config = bincode::config::standard();
+    let bincode_fixed_int_config = bincode::config::standard().with_fixed_int_encoding();
+    let bincode_big_endian_config = bincode::config::standard().with_big_endian();
+
+    // Pre-serialize data for all formats
+    let rkyv_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
+        .collect();
+
+    let bincode_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
+        .collect();
+
+    let bincode_fixed_int_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_fixed_int_config).unwrap())
+        .collect();
+
+    let bincode_big_endian_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_big_endian_config).unwrap())
+        .collect();
+
+    let postcard_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| postcard::to_stdvec(block).unwrap())
+        .collect();
+
+    let postcard_cobs_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| postcard::to_stdvec_cobs(block).unwrap())
+        .collect();
+
+    let postcard_crc32_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| postcard::to_stdvec_crc32(block, POSTCARD_CRC.digest()).unwrap())
+        .collect();
+
+    let messagepack_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| rmp_serde::to_vec(block).unwrap())
+        .collect();
+
+    let cbor_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(block, &mut bytes).unwrap();
+            bytes
+        })
+        .collect();
+
+    let bitcode_blocks: Vec<_> = test_data.iter().map(bitcode::encode).collect();
+
+    let borsh_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| borsh::to_vec(block).unwrap())
+        .collect();
+
+    let speedy_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.write_to_vec().unwrap())
+        .collect();
+
+    let capnp_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+
+    let capnp_packed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            let mut bytes = Vec::new();
+            capnp::serialize_packed::write_message(&mut bytes, &message).unwrap();
+            bytes
+        })
+        .collect();
+
+    let capnp_flat_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp_flat(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+
+    let flatbuffers_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut builder = FlatBufferBuilder::new();
+            block.to_flatbuffers(&mut builder);
+            builder.finished_data().to_vec()
+        })
+        .collect();
+
+    let protobuf_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto().encode_to_vec())
+        .collect();
+
+    let protobuf_fixed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto_fixed().encode_to_vec())
+        .collect();
+
+    let protobuf_packed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto_packed().encode_to_vec())
+        .collect();
+
+    let protobuf_packed_fixed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto_packed_fixed().encode_to_vec())
+        .collect();
+
+    let manual_blocks: Vec<_> = test_data.iter().map(manual_zerocopy::serialize).collect();
+
+    let manual_v2_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v2::serialize)
+        .collect();
+
+    let manual_v3_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v3::serialize)
+        .collect();
+    let manual_v4_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v4::serialize)
+        .collect();
+    let manual_bitpacked_blocks: Vec<_> =
+        test_data.iter().map(manual_bitpacked::serialize).collect();
+    let manual_elias_fano_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| manual_elias_fano::serialize(block).unwrap())
+        .collect();
+    let manual_roaring_blocks: Vec<_> = test_data.iter().map(manual_roaring::serialize).collect();
+
+    let deletion_ratio = deletion_ratio();
+    let live_docs = (deletion_ratio > 0.0).then(|| generate_live_docs(&test_data, deletion_ratio));
+
+    let mut group = c.benchmark_group(with_deletion_suffix("full_read", deletion_ratio));
+
+    group.bench_function("rkyv", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&rkyv_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block =
+                    rkyv::from_bytes::<Block, rkyv::rancor::Error>(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("bincode", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&bincode_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let (block, _len): (Block, usize) =
+                    bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("bincode_fixed_int", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&bincode_fixed_int_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let (block, _len): (Block, usize) =
+                    bincode::decode_from_slice(serialized_block, bincode_fixed_int_config).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("bincode_big_endian", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&bincode_big_endian_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let (block, _len): (Block, usize) =
+                    bincode::decode_from_slice(serialized_block, bincode_big_endian_config)
+                        .unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("bincode_borrow", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&bincode_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let (block, _len): (Block, usize) =
+                    bincode::borrow_decode_from_slice(serialized_block, bincode_config).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("postcard", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&postcard_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block = postcard::from_bytes(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("postcard_cobs", |b| {
+        // COBS decodes in place, so each frame is copied into a reusable receive buffer
+        let mut frame = Vec::new();
+
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&postcard_cobs_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                frame.clear();
+                frame.extend_from_slice(serialized_block);
+                let block: Block = postcard::from_bytes_cobs(&mut frame).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("postcard_crc32", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&postcard_crc32_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block =
+                    postcard::from_bytes_crc32(serialized_block, POSTCARD_CRC.digest()).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("messagepack", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&messagepack_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block = rmp_serde::from_slice(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("cbor", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&cbor_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block = ciborium::from_reader(&serialized_block[..]).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("bitcode", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&bitcode_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block = bitcode::decode(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("borsh", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&borsh_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block = borsh::from_slice(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("speedy", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&speedy_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block: Block = Block::read_from_buffer(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("capnp", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&capnp_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let reader = capnp::serialize::read_message_from_flat_slice(
+                    &mut &serialized_block[..],
+                    ReaderOptions::new(),
+                )
+                .unwrap();
+                let block =
+                    Block::from_capnp(reader.get_root::<block_capnp::block::Reader>().unwrap())
+                        .unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("capnp_packed", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&capnp_packed_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                // Packed messages must be unpacked into owned segments before reading
+                let reader = capnp::serialize_packed::read_message(
+                    &mut &serialized_block[..],
+                    ReaderOptions::new(),
+                )
+                .unwrap();
+                let block =
+                    Block::from_capnp(reader.get_root::<block_capnp::block::Reader>().unwrap())
+                        .unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("capnp_flat", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&capnp_flat_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let reader = capnp::serialize::read_message_from_flat_slice(
+                    &mut &serialized_block[..],
+                    ReaderOptions::new(),
+                )
+                .unwrap();
+                let block = Block::from_capnp_flat(
+                    reader
+                        .get_root::<block_flat_capnp::block::Reader>()
+                        .unwrap(),
+                )
+                .unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("flatbuffers", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&flatbuffers_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block =
+                    Block::from_flatbuffers(block_fbs::root_as_block(serialized_block).unwrap());
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("protobuf", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&protobuf_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let proto_block = block_proto::Block::decode(&serialized_block[..]).unwrap();
+                let block = Block::from_proto(&proto_block);
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("protobuf_fixed", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&protobuf_fixed_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let proto_block = block_proto::FixedBlock::decode(&serialized_block[..]).unwrap();
+                let block = Block::from_proto_fixed(&proto_block);
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("protobuf_packed", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&protobuf_packed_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let proto_block = block_proto::PackedBlock::decode(&serialized_block[..]).unwrap();
+                let block = Block::from_proto_packed(&proto_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("protobuf_packed_fixed", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&protobuf_packed_fixed_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let proto_block =
+                    block_proto::PackedFixedBlock::decode(&serialized_block[..]).unwrap();
+                let block = Block::from_proto_packed_fixed(&proto_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_zerocopy", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_zerocopy::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_zerocopy_v2", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_v2_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_zerocopy_v2::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_zerocopy_v3", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_v3_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_zerocopy_v3::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_zerocopy_v4", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_v4_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_zerocopy_v4::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_bitpacked", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_bitpacked_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_bitpacked::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_elias_fano", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_elias_fano_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_elias_fano::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
+                    let _doc_id = term.doc_id;
+                    let _field_mask = term.field_mask;
+                    total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_roaring", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_live_docs) in black_box(&manual_roaring_blocks)
+                .iter()
+                .zip(each_block_live_docs(&live_docs))
+            {
+                let block = manual_roaring::deserialize(serialized_block).unwrap();
+
+                for_live!(term in &block.full_terms, block_live_docs, {
                     let _doc_id = term.doc_id;
                     let _field_mask = term.field_mask;
                     total_frequency += term.frequency;
+                });
+            }
+
+            total_frequency
+        });
+    });
+
+    group.finish();
+}
+
+fn benchmark_filtered_read(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let bincode_config = bincode::config::standard();
+    let bincode_fixed_int_config = bincode::config::standard().with_fixed_int_encoding();
+    let bincode_big_endian_config = bincode::config::standard().with_big_endian();
+
+    // Pre-serialize data for all formats
+    let rkyv_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
+        .collect();
+
+    let bincode_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
+        .collect();
+
+    let bincode_fixed_int_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_fixed_int_config).unwrap())
+        .collect();
+
+    let bincode_big_endian_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_big_endian_config).unwrap())
+        .collect();
+
+    let postcard_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| postcard::to_stdvec(block).unwrap())
+        .collect();
+
+    let postcard_cobs_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| postcard::to_stdvec_cobs(block).unwrap())
+        .collect();
+
+    let postcard_crc32_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| postcard::to_stdvec_crc32(block, POSTCARD_CRC.digest()).unwrap())
+        .collect();
+
+    let messagepack_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| rmp_serde::to_vec(block).unwrap())
+        .collect();
+
+    let cbor_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(block, &mut bytes).unwrap();
+            bytes
+        })
+        .collect();
+
+    let bitcode_blocks: Vec<_> = test_data.iter().map(bitcode::encode).collect();
+
+    let borsh_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| borsh::to_vec(block).unwrap())
+        .collect();
+
+    let speedy_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.write_to_vec().unwrap())
+        .collect();
+
+    let capnp_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+
+    let capnp_packed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            let mut bytes = Vec::new();
+            capnp::serialize_packed::write_message(&mut bytes, &message).unwrap();
+            bytes
+        })
+        .collect();
+
+    let capnp_flat_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp_flat(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+
+    let flatbuffers_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut builder = FlatBufferBuilder::new();
+            block.to_flatbuffers(&mut builder);
+            builder.finished_data().to_vec()
+        })
+        .collect();
+
+    let protobuf_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto().encode_to_vec())
+        .collect();
+
+    let protobuf_fixed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto_fixed().encode_to_vec())
+        .collect();
+
+    let protobuf_packed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto_packed().encode_to_vec())
+        .collect();
+
+    let protobuf_packed_fixed_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block.to_proto_packed_fixed().encode_to_vec())
+        .collect();
+
+    let manual_blocks: Vec<_> = test_data.iter().map(manual_zerocopy::serialize).collect();
+
+    let manual_v2_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v2::serialize)
+        .collect();
+
+    let manual_v3_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v3::serialize)
+        .collect();
+    let manual_v4_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v4::serialize)
+        .collect();
+    let manual_bitpacked_blocks: Vec<_> =
+        test_data.iter().map(manual_bitpacked::serialize).collect();
+    let manual_elias_fano_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| manual_elias_fano::serialize(block).unwrap())
+        .collect();
+    let manual_dictionary_blocks: Vec<_> =
+        test_data.iter().map(manual_dictionary::serialize).collect();
+    let manual_roaring_blocks: Vec<_> = test_data.iter().map(manual_roaring::serialize).collect();
+    let manual_summary_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block_summary::serialize_with_summary(block, manual_zerocopy::serialize))
+        .collect();
+    let capnp_summary_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp_with_summary(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+
+    let deletion_ratio = deletion_ratio();
+    let live_docs = (deletion_ratio > 0.0).then(|| generate_live_docs(&test_data, deletion_ratio));
+
+    for hit_rate in [0.1, 0.5, 0.9] {
+        let query_mask = create_query_mask(hit_rate);
+        let group_name = with_deletion_suffix(
+            &format!("filtered_read_{}%", (hit_rate * 100.0) as u32),
+            deletion_ratio,
+        );
+        print_skip_rate(&group_name, &test_data, query_mask);
+        let mut group = c.benchmark_group(&group_name);
+
+        group.bench_function("rkyv", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&rkyv_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let archived =
+                        rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(serialized_block)
+                            .unwrap();
+
+                    for_live!(archived_term in archived.full_terms.iter(), block_live_docs, {
+                        let field_mask = archived_term.field_mask;
+
+                        if field_mask & query_mask != 0 {
+                            let term =
+                                rkyv::deserialize::<FullTerm, rkyv::rancor::Error>(archived_term)
+                                    .unwrap();
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bincode", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&bincode_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let (block, _len): (Block, usize) =
+                        bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bincode_fixed_int", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&bincode_fixed_int_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let (block, _len): (Block, usize) =
+                        bincode::decode_from_slice(serialized_block, bincode_fixed_int_config)
+                            .unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bincode_big_endian", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&bincode_big_endian_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let (block, _len): (Block, usize) =
+                        bincode::decode_from_slice(serialized_block, bincode_big_endian_config)
+                            .unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bincode_borrow", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&bincode_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let (block, _len): (Block, usize) =
+                        bincode::borrow_decode_from_slice(serialized_block, bincode_config)
+                            .unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("postcard", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&postcard_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block = postcard::from_bytes(serialized_block).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("postcard_cobs", |b| {
+            // COBS decodes in place, so each frame is copied into a reusable receive buffer
+            let mut frame = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&postcard_cobs_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    frame.clear();
+                    frame.extend_from_slice(serialized_block);
+                    let block: Block = postcard::from_bytes_cobs(&mut frame).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("postcard_crc32", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&postcard_crc32_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block =
+                        postcard::from_bytes_crc32(serialized_block, POSTCARD_CRC.digest())
+                            .unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("messagepack", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&messagepack_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block = rmp_serde::from_slice(serialized_block).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("cbor", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&cbor_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block = ciborium::from_reader(&serialized_block[..]).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bitcode", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&bitcode_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block = bitcode::decode(serialized_block).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("borsh", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&borsh_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block = borsh::from_slice(serialized_block).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("speedy", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&speedy_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block: Block = Block::read_from_buffer(serialized_block).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&capnp_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let block_reader = capnp_view::BlockReader::new(
+                        reader.get_root::<block_capnp::block::Reader>().unwrap(),
+                    )
+                    .unwrap();
+
+                    for_live!(term_reader in block_reader.iter(), block_live_docs, {
+                        let field_mask = term_reader.field_mask().unwrap();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp_packed", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&capnp_packed_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    // Packed messages must be unpacked before the filter can be applied
+                    let reader = capnp::serialize_packed::read_message(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let block_reader = capnp_view::BlockReader::new(
+                        reader.get_root::<block_capnp::block::Reader>().unwrap(),
+                    )
+                    .unwrap();
+
+                    for_live!(term_reader in block_reader.iter(), block_live_docs, {
+                        let field_mask = term_reader.field_mask().unwrap();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp_flat", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&capnp_flat_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let block_reader = reader
+                        .get_root::<block_flat_capnp::block::Reader>()
+                        .unwrap();
+                    let terms_reader = block_reader.get_full_terms().unwrap();
+
+                    for_live!(term_reader in terms_reader.iter(), block_live_docs, {
+                        // The mask halves are inline, so no pointer needs to be followed
+                        let field_mask = ((term_reader.get_field_mask_high() as u128) << 64)
+                            | (term_reader.get_field_mask_low() as u128);
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.get_doc_id();
+                            let frequency = term_reader.get_frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("flatbuffers", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&flatbuffers_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let block = block_fbs::root_as_block(serialized_block).unwrap();
+
+                    for_live!(term in block.full_terms().iter().flatten(), block_live_docs, {
+                        // Only the inline mask struct is read until the filter matches
+                        let field_mask = term.field_mask().map_or(0, |mask| {
+                            ((mask.high() as u128) << 64) | (mask.low() as u128)
+                        });
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("protobuf", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&protobuf_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let proto_block = block_proto::Block::decode(&serialized_block[..]).unwrap();
+
+                    for_live!(term in &proto_block.full_terms, block_live_docs, {
+                        let field_mask = term
+                            .field_mask
+                            .map_or(0, |mask| ((mask.high as u128) << 64) | (mask.low as u128));
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("protobuf_fixed", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&protobuf_fixed_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let proto_block =
+                        block_proto::FixedBlock::decode(&serialized_block[..]).unwrap();
+
+                    for_live!(term in &proto_block.full_terms, block_live_docs, {
+                        let field_mask = term
+                            .field_mask
+                            .map_or(0, |mask| ((mask.high as u128) << 64) | (mask.low as u128));
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("protobuf_packed", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&protobuf_packed_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let proto_block =
+                        block_proto::PackedBlock::decode(&serialized_block[..]).unwrap();
+                    let masks = proto_block
+                        .field_mask_highs
+                        .iter()
+                        .zip(&proto_block.field_mask_lows);
+
+                    for_live!((i, (&high, &low)) in masks.enumerate(), block_live_docs, {
+                        let field_mask = ((high as u128) << 64) | (low as u128);
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = proto_block.doc_ids[i];
+                            total_frequency += proto_block.frequencies[i];
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("protobuf_packed_fixed", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&protobuf_packed_fixed_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let proto_block =
+                        block_proto::PackedFixedBlock::decode(&serialized_block[..]).unwrap();
+                    let masks = proto_block
+                        .field_mask_highs
+                        .iter()
+                        .zip(&proto_block.field_mask_lows);
+
+                    for_live!((i, (&high, &low)) in masks.enumerate(), block_live_docs, {
+                        let field_mask = ((high as u128) << 64) | (low as u128);
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = proto_block.doc_ids[i];
+                            total_frequency += proto_block.frequencies[i];
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_zerocopy::BlockReader::new(serialized_block).unwrap();
+
+                    for_live!(term_reader in reader.iter(), block_live_docs, {
+                        let field_mask = term_reader.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_v2", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_v2_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_zerocopy_v2::BlockReader::new(serialized_block).unwrap();
+
+                    for_live!(archived_term in reader.iter(), block_live_docs, {
+                        let field_mask = archived_term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = archived_term.doc_id();
+                            let frequency = archived_term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_v3", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_v3_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    // v3 requires full deserialization (no zero-copy reader)
+                    let block = manual_zerocopy_v3::deserialize(serialized_block).unwrap();
+
+                    for_live!(term in &block.full_terms, block_live_docs, {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_v4", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_v4_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_zerocopy_v4::BlockReader::new(serialized_block).unwrap();
+
+                    for_live!(term in reader.iter(), block_live_docs, {
+                        let field_mask = term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_bitpacked", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_bitpacked_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_bitpacked::BlockReader::new(serialized_block).unwrap();
+
+                    for_live!(term in reader.iter(), block_live_docs, {
+                        let field_mask = term.field_mask();
+
+                        // Doc ids and frequencies are only unpacked for matching terms
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_elias_fano", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_elias_fano_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_elias_fano::BlockReader::new(serialized_block).unwrap();
+
+                    for_live!(term in reader.iter(), block_live_docs, {
+                        let field_mask = term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_dictionary", |b| {
+            let mut hit_set = manual_dictionary::HitSet::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_dictionary_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_dictionary::BlockReader::new(serialized_block).unwrap();
+                    reader.evaluate(query_mask, &mut hit_set);
+
+                    for_live!(term in reader.iter(), block_live_docs, {
+                        if hit_set.contains(term.mask_index()) {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_roaring", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_roaring_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = manual_roaring::BlockReader::new(serialized_block).unwrap();
+
+                    // Union the bitmaps of the query bits instead of scanning masks
+                    for position in &reader.matching(query_mask).unwrap() {
+                        if block_live_docs
+                            .is_some_and(|live_docs| !live_docs.is_live(position as usize))
+                        {
+                            continue;
+                        }
+
+                        let _doc_id = reader.doc_id(position as usize);
+                        let frequency = reader.frequency(position as usize);
+                        total_frequency += frequency;
+                        matched_count += 1;
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_summary", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in black_box(&manual_summary_blocks)
+                    .iter()
+                    .zip(each_block_live_docs(&live_docs))
+                {
+                    let (summary, body) = BlockSummary::read(serialized_block).unwrap();
+                    if !summary.may_match(query_mask) {
+                        continue;
+                    }
+
+                    let reader = manual_zerocopy::BlockReader::new(body).unwrap();
+
+                    for_live!(term in reader.iter(), block_live_docs, {
+                        let field_mask = term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp_summary", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for (serialized_block, block_live_docs) in
+                    black_box(&capnp_summary_blocks).iter().zip(each_block_live_docs(&live_docs))
+                {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let root = reader
+                        .get_root::<block_capnp::summarized_block::Reader>()
+                        .unwrap();
+
+                    let summary = BlockSummary::from_capnp(root).unwrap();
+                    if !summary.may_match(query_mask) {
+                        continue;
+                    }
+                    let root = root.get_block().unwrap();
+
+                    for_live!(term_reader in capnp_view::BlockReader::new(root).unwrap().iter(), block_live_docs, {
+                        let field_mask = term_reader.field_mask().unwrap();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    });
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.finish();
+    }
+}
+
+fn benchmark_predicates(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let bincode_config = bincode::config::standard();
+
+    let rkyv_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
+        .collect();
+    let bincode_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
+        .collect();
+    let capnp_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+    let manual_blocks: Vec<_> = test_data.iter().map(manual_zerocopy::serialize).collect();
+    let manual_v4_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v4::serialize)
+        .collect();
+    let manual_bitpacked_blocks: Vec<_> =
+        test_data.iter().map(manual_bitpacked::serialize).collect();
+    let manual_dictionary_blocks: Vec<_> =
+        test_data.iter().map(manual_dictionary::serialize).collect();
+    let manual_roaring_blocks: Vec<_> = test_data.iter().map(manual_roaring::serialize).collect();
+
+    let predicates = [
+        ("any", Predicate::any(create_query_mask(0.1))),
+        ("all", Predicate::all(0b11)),
+        ("none", Predicate::none(0b11)),
+        (
+            "any_min_frequency",
+            Predicate::any(create_query_mask(0.1)).with_min_frequency(900),
+        ),
+    ];
+
+    for (name, predicate) in predicates {
+        let group_name = format!("predicate_{name}");
+
+        let num_terms: usize = test_data.iter().map(|block| block.full_terms.len()).sum();
+        let num_hits = test_data
+            .iter()
+            .flat_map(|block| &block.full_terms)
+            .filter(|term| predicate.matches(*term))
+            .count();
+        println!(
+            "{group_name}: {num_hits} of {num_terms} terms match ({:.1}%)",
+            num_hits as f64 / num_terms as f64 * 100.0
+        );
+
+        let mut group = c.benchmark_group(&group_name);
+
+        group.bench_function("rkyv", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&rkyv_blocks) {
+                    let archived =
+                        rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(serialized_block)
+                            .unwrap();
+
+                    for term in archived.full_terms.iter() {
+                        if predicate.matches(term) {
+                            let _doc_id = term.doc_id.to_native();
+                            total_frequency += term.frequency.to_native();
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bincode", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&bincode_blocks) {
+                    let (block, _len): (Block, usize) =
+                        bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
+
+                    for term in &block.full_terms {
+                        if predicate.matches(term) {
+                            let _doc_id = term.doc_id;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&capnp_blocks) {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let block_reader = capnp_view::BlockReader::new(
+                        reader.get_root::<block_capnp::block::Reader>().unwrap(),
+                    )
+                    .unwrap();
+
+                    for term in block_reader.iter() {
+                        if predicate.matches_mask(term.field_mask().unwrap())
+                            && predicate.matches_frequency(term.frequency())
+                        {
+                            let _doc_id = term.doc_id();
+                            total_frequency += term.frequency();
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_blocks) {
+                    let reader = manual_zerocopy::BlockReader::new(serialized_block).unwrap();
+
+                    for term in reader.iter() {
+                        if predicate.matches(&term) {
+                            let _doc_id = term.doc_id();
+                            total_frequency += term.frequency();
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_v4", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_v4_blocks) {
+                    let reader = manual_zerocopy_v4::BlockReader::new(serialized_block).unwrap();
+
+                    for term in reader.iter() {
+                        if predicate.matches(term) {
+                            let _doc_id = term.doc_id();
+                            total_frequency += term.frequency();
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_v4_batch", |b| {
+            let mut matches = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_v4_blocks) {
+                    let reader = manual_zerocopy_v4::BlockReader::new(serialized_block).unwrap();
+                    let terms = reader.terms();
+                    predicate.evaluate_batch(terms, &mut matches);
+
+                    for (word_index, &word) in matches.iter().enumerate() {
+                        let mut word = word;
+                        while word != 0 {
+                            let term = &terms[word_index * 64 + word.trailing_zeros() as usize];
+                            let _doc_id = term.doc_id();
+                            total_frequency += term.frequency();
+                            matched_count += 1;
+                            word &= word - 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_bitpacked", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_bitpacked_blocks) {
+                    let reader = manual_bitpacked::BlockReader::new(serialized_block).unwrap();
+
+                    for term in reader.iter() {
+                        if predicate.matches(&term) {
+                            let _doc_id = term.doc_id();
+                            total_frequency += term.frequency();
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_dictionary", |b| {
+            let mut hit_set = manual_dictionary::HitSet::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_dictionary_blocks) {
+                    let reader = manual_dictionary::BlockReader::new(serialized_block).unwrap();
+                    reader.evaluate_predicate(&predicate, &mut hit_set);
+
+                    for term in reader.iter() {
+                        if hit_set.contains(term.mask_index()) {
+                            let frequency = term.frequency();
+                            if predicate.matches_frequency(frequency) {
+                                let _doc_id = term.doc_id();
+                                total_frequency += frequency;
+                                matched_count += 1;
+                            }
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_roaring", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_roaring_blocks) {
+                    let reader = manual_roaring::BlockReader::new(serialized_block).unwrap();
+
+                    for position in &reader.matching_predicate(&predicate).unwrap() {
+                        let _doc_id = reader.doc_id(position as usize);
+                        total_frequency += reader.frequency(position as usize);
+                        matched_count += 1;
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.finish();
+    }
+}
+
+fn benchmark_compressed_read(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let bincode_config = bincode::config::standard();
+    let serialized = serialize_for_compression(&test_data);
+    let query_mask = create_query_mask(0.1);
+
+    for compression in COMPRESSIONS {
+        let compressed: Vec<_> = serialized
+            .iter()
+            .filter(|(codec, _)| COMPRESSED_READ_CODECS.contains(codec))
+            .map(|(_, blocks)| compress_blocks(blocks, compression))
+            .collect();
+        let [rkyv_blocks, bincode_blocks, postcard_blocks, capnp_blocks, flatbuffers_blocks, manual_blocks] =
+            &compressed[..]
+        else {
+            unreachable!("one entry per codec in COMPRESSED_READ_CODECS");
+        };
+
+        let group_name = format!("compressed_full_read_{}", compression.name());
+        let mut group = c.benchmark_group(&group_name);
+
+        group.bench_function("rkyv", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = rkyv::util::AlignedVec::<16>::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+
+                for compressed_block in black_box(rkyv_blocks) {
+                    // rkyv needs an aligned buffer, so decompress straight into one
+                    let len = decompressor.decompressed_len(compressed_block).unwrap();
+                    buffer.clear();
+                    buffer.resize(len, 0);
+                    decompressor
+                        .decompress_to_slice(compressed_block, &mut buffer)
+                        .unwrap();
+                    let block = rkyv::from_bytes::<Block, rkyv::rancor::Error>(&buffer).unwrap();
+
+                    for term in &block.full_terms {
+                        let _doc_id = term.doc_id;
+                        let _field_mask = term.field_mask;
+                        total_frequency += term.frequency;
+                    }
+                }
+
+                total_frequency
+            });
+        });
+
+        group.bench_function("bincode", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+
+                for compressed_block in black_box(bincode_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let (block, _len): (Block, usize) =
+                        bincode::decode_from_slice(&buffer, bincode_config).unwrap();
+
+                    for term in &block.full_terms {
+                        let _doc_id = term.doc_id;
+                        let _field_mask = term.field_mask;
+                        total_frequency += term.frequency;
+                    }
+                }
+
+                total_frequency
+            });
+        });
+
+        group.bench_function("postcard", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+
+                for compressed_block in black_box(postcard_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let block: Block = postcard::from_bytes(&buffer).unwrap();
+
+                    for term in &block.full_terms {
+                        let _doc_id = term.doc_id;
+                        let _field_mask = term.field_mask;
+                        total_frequency += term.frequency;
+                    }
+                }
+
+                total_frequency
+            });
+        });
+
+        group.bench_function("capnp", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+
+                for compressed_block in black_box(capnp_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &buffer[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let block =
+                        Block::from_capnp(reader.get_root::<block_capnp::block::Reader>().unwrap())
+                            .unwrap();
+
+                    for term in &block.full_terms {
+                        let _doc_id = term.doc_id;
+                        let _field_mask = term.field_mask;
+                        total_frequency += term.frequency;
+                    }
+                }
+
+                total_frequency
+            });
+        });
+
+        group.bench_function("flatbuffers", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+
+                for compressed_block in black_box(flatbuffers_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let block = Block::from_flatbuffers(block_fbs::root_as_block(&buffer).unwrap());
+
+                    for term in &block.full_terms {
+                        let _doc_id = term.doc_id;
+                        let _field_mask = term.field_mask;
+                        total_frequency += term.frequency;
+                    }
+                }
+
+                total_frequency
+            });
+        });
+
+        group.bench_function("manual_zerocopy", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+
+                for compressed_block in black_box(manual_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let block = manual_zerocopy::deserialize(&buffer).unwrap();
+
+                    for term in &block.full_terms {
+                        let _doc_id = term.doc_id;
+                        let _field_mask = term.field_mask;
+                        total_frequency += term.frequency;
+                    }
+                }
+
+                total_frequency
+            });
+        });
+
+        group.finish();
+
+        let group_name = format!("compressed_filtered_read_10%_{}", compression.name());
+        let mut group = c.benchmark_group(&group_name);
+
+        group.bench_function("rkyv", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = rkyv::util::AlignedVec::<16>::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for compressed_block in black_box(rkyv_blocks) {
+                    let len = decompressor.decompressed_len(compressed_block).unwrap();
+                    buffer.clear();
+                    buffer.resize(len, 0);
+                    decompressor
+                        .decompress_to_slice(compressed_block, &mut buffer)
+                        .unwrap();
+                    let archived =
+                        rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(&buffer).unwrap();
+
+                    for archived_term in archived.full_terms.iter() {
+                        let field_mask = archived_term.field_mask;
+
+                        if field_mask & query_mask != 0 {
+                            let term =
+                                rkyv::deserialize::<FullTerm, rkyv::rancor::Error>(archived_term)
+                                    .unwrap();
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("bincode", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for compressed_block in black_box(bincode_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let (block, _len): (Block, usize) =
+                        bincode::decode_from_slice(&buffer, bincode_config).unwrap();
+
+                    for term in &block.full_terms {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("postcard", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for compressed_block in black_box(postcard_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let block: Block = postcard::from_bytes(&buffer).unwrap();
+
+                    for term in &block.full_terms {
+                        if term.field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id;
+                            let _field_mask = term.field_mask;
+                            total_frequency += term.frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for compressed_block in black_box(capnp_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &buffer[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let block_reader = capnp_view::BlockReader::new(
+                        reader.get_root::<block_capnp::block::Reader>().unwrap(),
+                    )
+                    .unwrap();
+
+                    for term_reader in block_reader.iter() {
+                        let field_mask = term_reader.field_mask().unwrap();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("flatbuffers", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for compressed_block in black_box(flatbuffers_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let block = block_fbs::root_as_block(&buffer).unwrap();
+
+                    for term in block.full_terms().iter().flatten() {
+                        let field_mask = term.field_mask().map_or(0, |mask| {
+                            ((mask.high() as u128) << 64) | (mask.low() as u128)
+                        });
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy", |b| {
+            let mut decompressor = compression.decompressor().unwrap();
+            let mut buffer = Vec::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for compressed_block in black_box(manual_blocks) {
+                    decompressor
+                        .decompress_into(compressed_block, &mut buffer)
+                        .unwrap();
+                    let reader = manual_zerocopy::BlockReader::new(&buffer).unwrap();
+
+                    for term_reader in reader.iter() {
+                        let field_mask = term_reader.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.finish();
+    }
+}
+
+// Number of distinct field masks in the low-cardinality data set
+const DISTINCT_MASKS: usize = 16;
+
+// Number of pool masks each block of the clustered data set draws from
+const MASKS_PER_BLOCK: usize = 2;
+
+fn measure_low_cardinality_sizes() {
+    let test_data = generate_test_data_with_distinct_masks(DISTINCT_MASKS);
+
+    println!("\n=== Encoding Sizes ({DISTINCT_MASKS} distinct field masks) ===");
+
+    let manual_v4_size: usize = test_data
+        .iter()
+        .map(|block| manual_zerocopy_v4::serialize(block).len())
+        .sum();
+    print_size_stats("manual_zerocopy_v4", manual_v4_size);
+
+    let manual_dictionary_size: usize = test_data
+        .iter()
+        .map(|block| manual_dictionary::serialize(block).len())
+        .sum();
+    print_size_stats("manual_dictionary", manual_dictionary_size);
+    print_overhead_stats("manual_zerocopy_v4", manual_v4_size, manual_dictionary_size);
+
+    let manual_roaring_size: usize = test_data
+        .iter()
+        .map(|block| manual_roaring::serialize(block).len())
+        .sum();
+    print_size_stats("manual_roaring", manual_roaring_size);
+    print_overhead_stats("manual_zerocopy_v4", manual_v4_size, manual_roaring_size);
+
+    println!(); // Extra newline after all sizes
+}
+
+fn benchmark_low_cardinality_filtered_read(c: &mut Criterion) {
+    let test_data = generate_test_data_with_distinct_masks(DISTINCT_MASKS);
+
+    let manual_v4_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v4::serialize)
+        .collect();
+    let manual_dictionary_blocks: Vec<_> =
+        test_data.iter().map(manual_dictionary::serialize).collect();
+    let manual_roaring_blocks: Vec<_> = test_data.iter().map(manual_roaring::serialize).collect();
+
+    for hit_rate in [0.1, 0.5, 0.9] {
+        let query_mask = create_query_mask(hit_rate);
+        let group_name = format!(
+            "low_cardinality_filtered_read_{}%",
+            (hit_rate * 100.0) as u32
+        );
+        let mut group = c.benchmark_group(&group_name);
+
+        group.bench_function("manual_zerocopy_v4", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_v4_blocks) {
+                    let reader = manual_zerocopy_v4::BlockReader::new(serialized_block).unwrap();
+
+                    for term in reader.iter() {
+                        let field_mask = term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_dictionary", |b| {
+            let mut hit_set = manual_dictionary::HitSet::new();
+
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_dictionary_blocks) {
+                    let reader = manual_dictionary::BlockReader::new(serialized_block).unwrap();
+                    reader.evaluate(query_mask, &mut hit_set);
+
+                    for term in reader.iter() {
+                        if hit_set.contains(term.mask_index()) {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_roaring", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_roaring_blocks) {
+                    let reader = manual_roaring::BlockReader::new(serialized_block).unwrap();
+
+                    for position in &reader.matching(query_mask).unwrap() {
+                        let _doc_id = reader.doc_id(position as usize);
+                        let frequency = reader.frequency(position as usize);
+                        total_frequency += frequency;
+                        matched_count += 1;
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.finish();
+    }
+}
+
+fn benchmark_clustered_filtered_read(c: &mut Criterion) {
+    let test_data = generate_test_data_with_clustered_masks(DISTINCT_MASKS, MASKS_PER_BLOCK);
+
+    let manual_v4_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v4::serialize)
+        .collect();
+    let manual_v4_summary_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| block_summary::serialize_with_summary(block, manual_zerocopy_v4::serialize))
+        .collect();
+    let capnp_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+    let capnp_summary_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp_with_summary(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+
+    for hit_rate in [0.1, 0.5, 0.9] {
+        let query_mask = create_query_mask(hit_rate);
+        let group_name = format!("clustered_filtered_read_{}%", (hit_rate * 100.0) as u32);
+        print_skip_rate(&group_name, &test_data, query_mask);
+        let mut group = c.benchmark_group(&group_name);
+
+        group.bench_function("manual_zerocopy_v4", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_v4_blocks) {
+                    let reader = manual_zerocopy_v4::BlockReader::new(serialized_block).unwrap();
+
+                    for term in reader.iter() {
+                        let field_mask = term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("manual_zerocopy_v4_summary", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&manual_v4_summary_blocks) {
+                    let (summary, body) = BlockSummary::read(serialized_block).unwrap();
+                    if !summary.may_match(query_mask) {
+                        continue;
+                    }
+
+                    let reader = manual_zerocopy_v4::BlockReader::new(body).unwrap();
+
+                    for term in reader.iter() {
+                        let field_mask = term.field_mask();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term.doc_id();
+                            let frequency = term.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&capnp_blocks) {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let root = reader.get_root::<block_capnp::block::Reader>().unwrap();
+
+                    for term_reader in capnp_view::BlockReader::new(root).unwrap().iter() {
+                        let field_mask = term_reader.field_mask().unwrap();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.bench_function("capnp_summary", |b| {
+            b.iter(|| {
+                let mut total_frequency = 0u64;
+                let mut matched_count = 0usize;
+
+                for serialized_block in black_box(&capnp_summary_blocks) {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        ReaderOptions::new(),
+                    )
+                    .unwrap();
+                    let root = reader
+                        .get_root::<block_capnp::summarized_block::Reader>()
+                        .unwrap();
+
+                    let summary = BlockSummary::from_capnp(root).unwrap();
+                    if !summary.may_match(query_mask) {
+                        continue;
+                    }
+                    let root = root.get_block().unwrap();
+
+                    for term_reader in capnp_view::BlockReader::new(root).unwrap().iter() {
+                        let field_mask = term_reader.field_mask().unwrap();
+
+                        if field_mask & query_mask != 0 {
+                            let _doc_id = term_reader.doc_id();
+                            let frequency = term_reader.frequency();
+                            total_frequency += frequency;
+                            matched_count += 1;
+                        }
+                    }
+                }
+
+                (total_frequency, matched_count)
+            });
+        });
+
+        group.finish();
+    }
+}
+
+fn benchmark_doc_id_seek(c: &mut Criterion) {
+    let test_data = generate_test_data();
+
+    let manual_v4_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v4::serialize)
+        .collect();
+    let manual_bitpacked_blocks: Vec<_> =
+        test_data.iter().map(manual_bitpacked::serialize).collect();
+    let manual_elias_fano_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| manual_elias_fano::serialize(block).unwrap())
+        .collect();
+
+    // Seek to a doc id just past every 10th term, so some targets land in gaps
+    let targets: Vec<Vec<u64>> = test_data
+        .iter()
+        .map(|block| {
+            block
+                .full_terms
+                .iter()
+                .step_by(10)
+                .map(|term| term.doc_id + 1)
+                .collect()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("doc_id_seek");
+
+    group.bench_function("manual_zerocopy_v4_binary_search", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_targets) in
+                black_box(&manual_v4_blocks).iter().zip(&targets)
+            {
+                let reader = manual_zerocopy_v4::BlockReader::new(serialized_block).unwrap();
+                let terms = reader.terms();
+
+                for &target in block_targets {
+                    let index = terms.partition_point(|term| term.doc_id() < target);
+                    if let Some(term) = terms.get(index) {
+                        total_frequency += term.frequency();
+                    }
+                }
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_bitpacked_scan", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_targets) in
+                black_box(&manual_bitpacked_blocks).iter().zip(&targets)
+            {
+                let reader = manual_bitpacked::BlockReader::new(serialized_block).unwrap();
+
+                // Walk the block, unpacking one doc id per step
+                for &target in block_targets {
+                    if let Some(term) = reader.iter().find(|term| term.doc_id() >= target) {
+                        total_frequency += term.frequency();
+                    }
+                }
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_elias_fano_next_geq", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, block_targets) in
+                black_box(&manual_elias_fano_blocks).iter().zip(&targets)
+            {
+                let reader = manual_elias_fano::BlockReader::new(serialized_block).unwrap();
+
+                for &target in block_targets {
+                    if let Some(term) = reader.next_geq(target) {
+                        total_frequency += term.frequency();
+                    }
+                }
+            }
+
+            total_frequency
+        });
+    });
+
+    group.finish();
+}
+
+// Serialize every block of every posting list with one codec
+fn benchmark_doc_id_range(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let bincode_config = bincode::config::standard();
+
+    let rkyv_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
+        .collect();
+    let bincode_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
+        .collect();
+    let capnp_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
+    let manual_blocks: Vec<_> = test_data.iter().map(manual_zerocopy::serialize).collect();
+    let manual_v2_blocks: Vec<_> = test_data
+        .iter()
+        .map(manual_zerocopy_v2::serialize)
+        .collect();
+
+    // Query the middle 10% of every block's doc ids
+    let ranges: Vec<Range<u64>> = test_data
+        .iter()
+        .map(|block| block.full_terms[45].doc_id..block.full_terms[55].doc_id)
+        .collect();
+
+    let mut group = c.benchmark_group("doc_id_range");
+
+    group.bench_function("rkyv", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, range) in black_box(&rkyv_blocks).iter().zip(&ranges) {
+                let archived =
+                    rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(serialized_block).unwrap();
+
+                for term in archived.range(range.clone()) {
+                    total_frequency += term.frequency.to_native();
+                }
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("capnp", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, range) in black_box(&capnp_blocks).iter().zip(&ranges) {
+                let reader = capnp::serialize::read_message_from_flat_slice(
+                    &mut &serialized_block[..],
+                    ReaderOptions::new(),
+                )
+                .unwrap();
+                let block_reader = capnp_view::BlockReader::new(
+                    reader.get_root::<block_capnp::block::Reader>().unwrap(),
+                )
+                .unwrap();
+
+                for term in block_reader.range(range.clone()) {
+                    total_frequency += term.frequency();
+                }
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("manual_zerocopy", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, range) in black_box(&manual_blocks).iter().zip(&ranges) {
+                let reader = manual_zerocopy::BlockReader::new(serialized_block).unwrap();
+
+                for term in reader.range(range.clone()) {
+                    total_frequency += term.frequency();
                 }
             }
 
@@ -327,13 +4003,11 @@ fn benchmark_full_read(c: &mut Criterion) {
         b.iter(|| {
             let mut total_frequency = 0u64;
 
-            for serialized_block in black_box(&manual_v2_blocks) {
-                let block = manual_zerocopy_v2::deserialize(serialized_block).unwrap();
+            for (serialized_block, range) in black_box(&manual_v2_blocks).iter().zip(&ranges) {
+                let reader = manual_zerocopy_v2::BlockReader::new(serialized_block).unwrap();
 
-                for term in &block.full_terms {
-                    let _doc_id = term.doc_id;
-                    let _field_mask = term.field_mask;
-                    total_frequency += term.frequency;
+                for term in reader.range(range.clone()) {
+                    total_frequency += term.frequency();
                 }
             }
 
@@ -341,17 +4015,37 @@ fn benchmark_full_read(c: &mut Criterion) {
         });
     });
 
-    group.bench_function("manual_zerocopy_v3", |b| {
+    // Baselines that test every term's doc id instead of binary-searching
+    group.bench_function("manual_zerocopy_scan", |b| {
         b.iter(|| {
             let mut total_frequency = 0u64;
 
-            for serialized_block in black_box(&manual_v3_blocks) {
-                let block = manual_zerocopy_v3::deserialize(serialized_block).unwrap();
+            for (serialized_block, range) in black_box(&manual_blocks).iter().zip(&ranges) {
+                let reader = manual_zerocopy::BlockReader::new(serialized_block).unwrap();
+
+                for term in reader.iter() {
+                    if range.contains(&term.doc_id()) {
+                        total_frequency += term.frequency();
+                    }
+                }
+            }
+
+            total_frequency
+        });
+    });
+
+    group.bench_function("bincode", |b| {
+        b.iter(|| {
+            let mut total_frequency = 0u64;
+
+            for (serialized_block, range) in black_box(&bincode_blocks).iter().zip(&ranges) {
+                let (block, _len): (Block, usize) =
+                    bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
 
                 for term in &block.full_terms {
-                    let _doc_id = term.doc_id;
-                    let _field_mask = term.field_mask;
-                    total_frequency += term.frequency;
+                    if range.contains(&term.doc_id) {
+                        total_frequency += term.frequency;
+                    }
                 }
             }
 
@@ -362,26 +4056,339 @@ fn benchmark_full_read(c: &mut Criterion) {
     group.finish();
 }
 
-fn benchmark_filtered_read(c: &mut Criterion) {
-    let test_data = generate_test_data();
+fn serialize_lists<B>(lists: &[Vec<Block>], serialize: impl Fn(&Block) -> B) -> Vec<Vec<B>> {
+    lists
+        .iter()
+        .map(|blocks| blocks.iter().map(&serialize).collect())
+        .collect()
+}
+
+type Combine<B> = fn(&[&[B]]) -> Result<Postings, &'static str>;
+
+fn bench_postings<B: AsRef<[u8]>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    lists: &[Vec<B>],
+    combine: Combine<B>,
+) {
+    let lists: Vec<&[B]> = lists.iter().map(Vec::as_slice).collect();
+
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            let result = combine(black_box(&lists)).unwrap();
+            let total_frequency: u64 = result.postings.iter().map(|p| p.frequency).sum();
+
+            (total_frequency, result.postings.len())
+        });
+    });
+}
+
+fn benchmark_postings(c: &mut Criterion) {
+    // A common, a medium and a rare term over the same doc id space
+    let lists = [
+        generate_postings(1, 1_000_000, 0.5),
+        generate_postings(2, 1_000_000, 0.1),
+        generate_postings(3, 1_000_000, 0.001),
+    ];
+
+    let rkyv_lists = serialize_lists(&lists, |block| {
+        rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap()
+    });
+    let bincode_lists = serialize_lists(&lists, |block| {
+        bincode::encode_to_vec(block, bincode::config::standard()).unwrap()
+    });
+    let postcard_lists = serialize_lists(&lists, |block| postcard::to_stdvec(block).unwrap());
+    let messagepack_lists = serialize_lists(&lists, |block| rmp_serde::to_vec(block).unwrap());
+    let cbor_lists = serialize_lists(&lists, |block| {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(block, &mut bytes).unwrap();
+        bytes
+    });
+    let bitcode_lists = serialize_lists(&lists, bitcode::encode);
+    let borsh_lists = serialize_lists(&lists, |block| borsh::to_vec(block).unwrap());
+    let speedy_lists = serialize_lists(&lists, |block| block.write_to_vec().unwrap());
+    let capnp_lists = serialize_lists(&lists, |block| {
+        let mut message = Builder::new_default();
+        block.to_capnp(&mut message);
+        capnp::serialize::write_message_to_words(&message)
+    });
+    let flatbuffers_lists = serialize_lists(&lists, |block| {
+        let mut builder = FlatBufferBuilder::new();
+        block.to_flatbuffers(&mut builder);
+        builder.finished_data().to_vec()
+    });
+    let protobuf_lists = serialize_lists(&lists, |block| block.to_proto().encode_to_vec());
+    let manual_lists = serialize_lists(&lists, manual_zerocopy::serialize);
+    let manual_v2_lists = serialize_lists(&lists, manual_zerocopy_v2::serialize);
+    let manual_v4_lists = serialize_lists(&lists, manual_zerocopy_v4::serialize);
+    let manual_bitpacked_lists = serialize_lists(&lists, manual_bitpacked::serialize);
+    let manual_elias_fano_lists =
+        serialize_lists(&lists, |block| manual_elias_fano::serialize(block).unwrap());
+    let manual_dictionary_lists = serialize_lists(&lists, manual_dictionary::serialize);
+    let manual_roaring_lists = serialize_lists(&lists, manual_roaring::serialize);
+
+    // AND of all three terms: the rare term lets the others skip most blocks
+    let mut group = c.benchmark_group("postings_and");
+
+    bench_postings(
+        &mut group,
+        "rkyv",
+        &rkyv_lists,
+        postings::intersect::<postings::Rkyv, _>,
+    );
+    bench_postings(
+        &mut group,
+        "bincode",
+        &bincode_lists,
+        postings::intersect::<postings::Bincode, _>,
+    );
+    bench_postings(
+        &mut group,
+        "postcard",
+        &postcard_lists,
+        postings::intersect::<postings::Postcard, _>,
+    );
+    bench_postings(
+        &mut group,
+        "messagepack",
+        &messagepack_lists,
+        postings::intersect::<postings::MessagePack, _>,
+    );
+    bench_postings(
+        &mut group,
+        "cbor",
+        &cbor_lists,
+        postings::intersect::<postings::Cbor, _>,
+    );
+    bench_postings(
+        &mut group,
+        "bitcode",
+        &bitcode_lists,
+        postings::intersect::<postings::Bitcode, _>,
+    );
+    bench_postings(
+        &mut group,
+        "borsh",
+        &borsh_lists,
+        postings::intersect::<postings::Borsh, _>,
+    );
+    bench_postings(
+        &mut group,
+        "speedy",
+        &speedy_lists,
+        postings::intersect::<postings::Speedy, _>,
+    );
+    bench_postings(
+        &mut group,
+        "capnp",
+        &capnp_lists,
+        postings::intersect::<postings::Capnp, _>,
+    );
+    bench_postings(
+        &mut group,
+        "flatbuffers",
+        &flatbuffers_lists,
+        postings::intersect::<postings::Flatbuffers, _>,
+    );
+    bench_postings(
+        &mut group,
+        "protobuf",
+        &protobuf_lists,
+        postings::intersect::<postings::Protobuf, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_zerocopy",
+        &manual_lists,
+        postings::intersect::<postings::ManualZerocopy, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_zerocopy_v2",
+        &manual_v2_lists,
+        postings::intersect::<postings::ManualZerocopyV2, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_zerocopy_v4",
+        &manual_v4_lists,
+        postings::intersect::<postings::ManualZerocopyV4, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_bitpacked",
+        &manual_bitpacked_lists,
+        postings::intersect::<postings::ManualBitpacked, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_elias_fano",
+        &manual_elias_fano_lists,
+        postings::intersect::<postings::ManualEliasFano, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_dictionary",
+        &manual_dictionary_lists,
+        postings::intersect::<postings::ManualDictionary, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_roaring",
+        &manual_roaring_lists,
+        postings::intersect::<postings::ManualRoaring, _>,
+    );
+
+    group.finish();
+
+    // OR of the common and medium terms, which has to read every block
+    let mut group = c.benchmark_group("postings_or");
+
+    bench_postings(
+        &mut group,
+        "rkyv",
+        &rkyv_lists[..2],
+        postings::union::<postings::Rkyv, _>,
+    );
+    bench_postings(
+        &mut group,
+        "bincode",
+        &bincode_lists[..2],
+        postings::union::<postings::Bincode, _>,
+    );
+    bench_postings(
+        &mut group,
+        "postcard",
+        &postcard_lists[..2],
+        postings::union::<postings::Postcard, _>,
+    );
+    bench_postings(
+        &mut group,
+        "messagepack",
+        &messagepack_lists[..2],
+        postings::union::<postings::MessagePack, _>,
+    );
+    bench_postings(
+        &mut group,
+        "cbor",
+        &cbor_lists[..2],
+        postings::union::<postings::Cbor, _>,
+    );
+    bench_postings(
+        &mut group,
+        "bitcode",
+        &bitcode_lists[..2],
+        postings::union::<postings::Bitcode, _>,
+    );
+    bench_postings(
+        &mut group,
+        "borsh",
+        &borsh_lists[..2],
+        postings::union::<postings::Borsh, _>,
+    );
+    bench_postings(
+        &mut group,
+        "speedy",
+        &speedy_lists[..2],
+        postings::union::<postings::Speedy, _>,
+    );
+    bench_postings(
+        &mut group,
+        "capnp",
+        &capnp_lists[..2],
+        postings::union::<postings::Capnp, _>,
+    );
+    bench_postings(
+        &mut group,
+        "flatbuffers",
+        &flatbuffers_lists[..2],
+        postings::union::<postings::Flatbuffers, _>,
+    );
+    bench_postings(
+        &mut group,
+        "protobuf",
+        &protobuf_lists[..2],
+        postings::union::<postings::Protobuf, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_zerocopy",
+        &manual_lists[..2],
+        postings::union::<postings::ManualZerocopy, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_zerocopy_v2",
+        &manual_v2_lists[..2],
+        postings::union::<postings::ManualZerocopyV2, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_zerocopy_v4",
+        &manual_v4_lists[..2],
+        postings::union::<postings::ManualZerocopyV4, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_bitpacked",
+        &manual_bitpacked_lists[..2],
+        postings::union::<postings::ManualBitpacked, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_elias_fano",
+        &manual_elias_fano_lists[..2],
+        postings::union::<postings::ManualEliasFano, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_dictionary",
+        &manual_dictionary_lists[..2],
+        postings::union::<postings::ManualDictionary, _>,
+    );
+    bench_postings(
+        &mut group,
+        "manual_roaring",
+        &manual_roaring_lists[..2],
+        postings::union::<postings::ManualRoaring, _>,
+    );
+
+    group.finish();
+}
+
+fn benchmark_top_k(c: &mut Criterion) {
+    const K: usize = 10;
+
+    let test_data = generate_test_data_with_distinct_masks(DISTINCT_MASKS);
     let bincode_config = bincode::config::standard();
 
-    // Pre-serialize data for all formats
+    // A single field bit only matches a few of the pooled masks
+    let first_mask = test_data[0].full_terms[0].field_mask;
+    let predicate = Predicate::any(1u128 << first_mask.trailing_zeros());
+
+    let num_terms: usize = test_data.iter().map(|block| block.full_terms.len()).sum();
+    let num_hits = test_data
+        .iter()
+        .flat_map(|block| &block.full_terms)
+        .filter(|term| predicate.matches(*term))
+        .count();
+    println!(
+        "\n=== Top-{K} query: {num_hits} of {num_terms} terms match ({:.2}%) ===\n",
+        num_hits as f64 / num_terms as f64 * 100.0
+    );
+
     let rkyv_blocks: Vec<_> = test_data
         .iter()
         .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
         .collect();
-
     let bincode_blocks: Vec<_> = test_data
         .iter()
         .map(|block| bincode::encode_to_vec(block, bincode_config).unwrap())
         .collect();
-
     let postcard_blocks: Vec<_> = test_data
         .iter()
         .map(|block| postcard::to_stdvec(block).unwrap())
         .collect();
-
     let capnp_blocks: Vec<_> = test_data
         .iter()
         .map(|block| {
@@ -390,213 +4397,455 @@ fn benchmark_filtered_read(c: &mut Criterion) {
             capnp::serialize::write_message_to_words(&message)
         })
         .collect();
-
-    let manual_blocks: Vec<_> = test_data
+    let manual_blocks: Vec<_> = test_data.iter().map(manual_zerocopy::serialize).collect();
+    let manual_v4_blocks: Vec<_> = test_data
         .iter()
-        .map(|block| manual_zerocopy::serialize(block))
+        .map(manual_zerocopy_v4::serialize)
         .collect();
 
-    let manual_v2_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| manual_zerocopy_v2::serialize(block))
-        .collect();
+    let mut group = c.benchmark_group("top_k_selective");
 
-    let manual_v3_blocks: Vec<_> = test_data
-        .iter()
-        .map(|block| manual_zerocopy_v3::serialize(block))
-        .collect();
+    // Zero-copy readers: non-matching terms never have their doc id read
+    group.bench_function("rkyv", |b| {
+        b.iter(|| top_k::rkyv(black_box(&rkyv_blocks), K, &predicate).unwrap());
+    });
 
-    for hit_rate in [0.1, 0.5, 0.9] {
-        let query_mask = create_query_mask(hit_rate);
-        let group_name = format!("filtered_read_{}%", (hit_rate * 100.0) as u32);
-        let mut group = c.benchmark_group(&group_name);
+    group.bench_function("capnp", |b| {
+        b.iter(|| top_k::capnp(black_box(&capnp_blocks), K, &predicate).unwrap());
+    });
 
-        group.bench_function("rkyv", |b| {
-            b.iter(|| {
-                let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
+    group.bench_function("manual_zerocopy", |b| {
+        b.iter(|| top_k::manual_zerocopy(black_box(&manual_blocks), K, &predicate).unwrap());
+    });
 
-                for serialized_block in black_box(&rkyv_blocks) {
-                    let archived =
-                        rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(serialized_block)
-                            .unwrap();
+    group.bench_function("manual_zerocopy_v4", |b| {
+        b.iter(|| top_k::manual_zerocopy_v4(black_box(&manual_v4_blocks), K, &predicate).unwrap());
+    });
 
-                    for archived_term in archived.full_terms.iter() {
-                        let field_mask = archived_term.field_mask;
+    // Decode-first: every block is fully deserialized before filtering
+    group.bench_function("rkyv_decode", |b| {
+        b.iter(|| {
+            let mut top_k = TopK::new(K);
 
-                        if field_mask & query_mask != 0 {
-                            let term =
-                                rkyv::deserialize::<FullTerm, rkyv::rancor::Error>(archived_term)
-                                    .unwrap();
-                            let _doc_id = term.doc_id;
-                            let _field_mask = term.field_mask;
-                            total_frequency += term.frequency;
-                            matched_count += 1;
-                        }
-                    }
-                }
+            for serialized_block in black_box(&rkyv_blocks) {
+                let block =
+                    rkyv::from_bytes::<Block, rkyv::rancor::Error>(serialized_block).unwrap();
+                top_k.push_block(&block, &predicate);
+            }
 
-                (total_frequency, matched_count)
-            });
+            top_k.into_sorted_vec()
         });
+    });
 
-        group.bench_function("bincode", |b| {
-            b.iter(|| {
-                let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
+    group.bench_function("bincode", |b| {
+        b.iter(|| {
+            let mut top_k = TopK::new(K);
 
-                for serialized_block in black_box(&bincode_blocks) {
-                    let (block, _len): (Block, usize) =
-                        bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
+            for serialized_block in black_box(&bincode_blocks) {
+                let (block, _len): (Block, usize) =
+                    bincode::decode_from_slice(serialized_block, bincode_config).unwrap();
+                top_k.push_block(&block, &predicate);
+            }
 
-                    for term in &block.full_terms {
-                        if term.field_mask & query_mask != 0 {
-                            let _doc_id = term.doc_id;
-                            let _field_mask = term.field_mask;
-                            total_frequency += term.frequency;
-                            matched_count += 1;
-                        }
-                    }
-                }
+            top_k.into_sorted_vec()
+        });
+    });
 
-                (total_frequency, matched_count)
-            });
+    group.bench_function("postcard", |b| {
+        b.iter(|| {
+            let mut top_k = TopK::new(K);
+
+            for serialized_block in black_box(&postcard_blocks) {
+                let block: Block = postcard::from_bytes(serialized_block).unwrap();
+                top_k.push_block(&block, &predicate);
+            }
+
+            top_k.into_sorted_vec()
         });
+    });
 
-        group.bench_function("postcard", |b| {
-            b.iter(|| {
-                let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
+    group.bench_function("capnp_decode", |b| {
+        b.iter(|| {
+            let mut top_k = TopK::new(K);
 
-                for serialized_block in black_box(&postcard_blocks) {
-                    let block: Block = postcard::from_bytes(serialized_block).unwrap();
+            for serialized_block in black_box(&capnp_blocks) {
+                let reader = capnp::serialize::read_message_from_flat_slice(
+                    &mut &serialized_block[..],
+                    ReaderOptions::new(),
+                )
+                .unwrap();
+                let block =
+                    Block::from_capnp(reader.get_root::<block_capnp::block::Reader>().unwrap())
+                        .unwrap();
+                top_k.push_block(&block, &predicate);
+            }
 
-                    for term in &block.full_terms {
-                        if term.field_mask & query_mask != 0 {
-                            let _doc_id = term.doc_id;
-                            let _field_mask = term.field_mask;
-                            total_frequency += term.frequency;
-                            matched_count += 1;
-                        }
-                    }
-                }
+            top_k.into_sorted_vec()
+        });
+    });
 
-                (total_frequency, matched_count)
-            });
+    group.bench_function("manual_zerocopy_v4_decode", |b| {
+        b.iter(|| {
+            let mut top_k = TopK::new(K);
+
+            for serialized_block in black_box(&manual_v4_blocks) {
+                let block = manual_zerocopy_v4::deserialize(serialized_block).unwrap();
+                top_k.push_block(&block, &predicate);
+            }
+
+            top_k.into_sorted_vec()
         });
+    });
 
-        group.bench_function("capnp", |b| {
-            b.iter(|| {
-                let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
+    group.finish();
+}
 
-                for serialized_block in black_box(&capnp_blocks) {
-                    let reader = capnp::serialize::read_message_from_flat_slice(
-                        &mut &serialized_block[..],
-                        ReaderOptions::new(),
-                    )
-                    .unwrap();
-                    let block_reader = reader.get_root::<block_capnp::block::Reader>().unwrap();
-                    let terms_reader = block_reader.get_full_terms().unwrap();
+// Helper function to deal the terms of the test data into `num_segments`
+// segments at random, so their doc ids interleave, in blocks of 100 terms
+fn split_into_segments(test_data: &[Block], num_segments: usize) -> Vec<Vec<Block>> {
+    let mut rng = Xorshift64::new(17);
+    let mut segment_terms: Vec<Vec<FullTerm>> = vec![Vec::new(); num_segments];
 
-                    for term_reader in terms_reader.iter() {
-                        let mask_reader = term_reader.get_field_mask().unwrap();
-                        let field_mask = ((mask_reader.get_high() as u128) << 64)
-                            | (mask_reader.get_low() as u128);
+    for term in test_data.iter().flat_map(|block| &block.full_terms) {
+        segment_terms[(rng.next() % num_segments as u64) as usize].push(term.clone());
+    }
 
-                        if field_mask & query_mask != 0 {
-                            let _doc_id = term_reader.get_doc_id();
-                            let frequency = term_reader.get_frequency();
-                            total_frequency += frequency;
-                            matched_count += 1;
-                        }
-                    }
-                }
+    segment_terms
+        .iter()
+        .map(|terms| {
+            terms
+                .chunks(100)
+                .map(|chunk| Block {
+                    full_terms: chunk.to_vec(),
+                })
+                .collect()
+        })
+        .collect()
+}
 
-                (total_frequency, matched_count)
-            });
-        });
+type MergeSegments<B> = fn(&[Segment<B>]) -> Result<Vec<Vec<u8>>, &'static str>;
 
-        group.bench_function("manual_zerocopy", |b| {
-            b.iter(|| {
-                let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
+fn bench_merge<B: AsRef<[u8]>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    segments: &[Vec<B>],
+    live_docs: &[Vec<LiveDocs>],
+    merge_segments: MergeSegments<B>,
+) {
+    let segments: Vec<Segment<B>> = segments
+        .iter()
+        .zip(live_docs)
+        .map(|(blocks, live_docs)| Segment::with_live_docs(blocks, live_docs))
+        .collect();
 
-                for serialized_block in black_box(&manual_blocks) {
-                    let reader = manual_zerocopy::BlockReader::new(serialized_block).unwrap();
+    group.bench_function(name, |b| {
+        b.iter(|| merge_segments(black_box(&segments)).unwrap().len());
+    });
+}
 
-                    for term_reader in reader.iter() {
-                        let field_mask = term_reader.field_mask();
+fn benchmark_segment_merge(c: &mut Criterion) {
+    const NUM_SEGMENTS: usize = 4;
+    const DELETION_RATIO: f64 = 0.1;
+    const BLOCK_SIZE: usize = 100;
 
-                        if field_mask & query_mask != 0 {
-                            let _doc_id = term_reader.doc_id();
-                            let frequency = term_reader.frequency();
-                            total_frequency += frequency;
-                            matched_count += 1;
-                        }
-                    }
-                }
+    let segments = split_into_segments(&generate_test_data(), NUM_SEGMENTS);
+    let live_docs: Vec<Vec<LiveDocs>> = segments
+        .iter()
+        .map(|blocks| generate_live_docs(blocks, DELETION_RATIO))
+        .collect();
+    let num_terms: usize = segments
+        .iter()
+        .flatten()
+        .map(|block| block.full_terms.len())
+        .sum();
 
-                (total_frequency, matched_count)
-            });
-        });
+    let rkyv_segments = serialize_lists(&segments, |block| {
+        rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap()
+    });
+    let bincode_segments = serialize_lists(&segments, |block| {
+        bincode::encode_to_vec(block, bincode::config::standard()).unwrap()
+    });
+    let postcard_segments = serialize_lists(&segments, |block| postcard::to_stdvec(block).unwrap());
+    let capnp_segments = serialize_lists(&segments, |block| {
+        let mut message = Builder::new_default();
+        block.to_capnp(&mut message);
+        capnp::serialize::write_message_to_words(&message)
+    });
+    let protobuf_segments = serialize_lists(&segments, |block| block.to_proto().encode_to_vec());
+    let manual_segments = serialize_lists(&segments, manual_zerocopy::serialize);
+    let manual_v4_segments = serialize_lists(&segments, manual_zerocopy_v4::serialize);
+    let manual_bitpacked_segments = serialize_lists(&segments, manual_bitpacked::serialize);
+    let manual_elias_fano_segments = serialize_lists(&segments, |block| {
+        manual_elias_fano::serialize(block).unwrap()
+    });
+    let manual_roaring_segments = serialize_lists(&segments, manual_roaring::serialize);
 
-        group.bench_function("manual_zerocopy_v2", |b| {
-            b.iter(|| {
-                let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
+    // Throughput counts input terms, deleted ones included
+    let mut group = c.benchmark_group("segment_merge");
+    group.throughput(Throughput::Elements(num_terms as u64));
 
-                for serialized_block in black_box(&manual_v2_blocks) {
-                    let reader = manual_zerocopy_v2::BlockReader::new(serialized_block).unwrap();
+    bench_merge(&mut group, "rkyv", &rkyv_segments, &live_docs, |segments| {
+        merge::merge(
+            segments,
+            BLOCK_SIZE,
+            |bytes| {
+                rkyv::from_bytes::<Block, rkyv::rancor::Error>(bytes)
+                    .map_err(|_| "Invalid rkyv block")
+            },
+            |block| {
+                rkyv::to_bytes::<rkyv::rancor::Error>(block)
+                    .unwrap()
+                    .to_vec()
+            },
+        )
+    });
+    bench_merge(
+        &mut group,
+        "bincode",
+        &bincode_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                |bytes| {
+                    bincode::decode_from_slice(bytes, bincode::config::standard())
+                        .map(|(block, _len)| block)
+                        .map_err(|_| "Invalid bincode block")
+                },
+                |block| bincode::encode_to_vec(block, bincode::config::standard()).unwrap(),
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "postcard",
+        &postcard_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                |bytes| postcard::from_bytes(bytes).map_err(|_| "Invalid postcard block"),
+                |block| postcard::to_stdvec(block).unwrap(),
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "capnp",
+        &capnp_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                |mut bytes| {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut bytes,
+                        ReaderOptions::new(),
+                    )
+                    .map_err(|_| "Invalid capnp message")?;
+                    reader
+                        .get_root::<block_capnp::block::Reader>()
+                        .and_then(Block::from_capnp)
+                        .map_err(|_| "Invalid capnp block")
+                },
+                |block| {
+                    let mut message = Builder::new_default();
+                    block.to_capnp(&mut message);
+                    capnp::serialize::write_message_to_words(&message)
+                },
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "protobuf",
+        &protobuf_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                |bytes| {
+                    block_proto::Block::decode(bytes)
+                        .map(|proto_block| Block::from_proto(&proto_block))
+                        .map_err(|_| "Invalid protobuf block")
+                },
+                |block| block.to_proto().encode_to_vec(),
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "manual_zerocopy",
+        &manual_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                manual_zerocopy::deserialize,
+                manual_zerocopy::serialize,
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "manual_zerocopy_fast_path",
+        &manual_segments,
+        &live_docs,
+        |segments| merge::merge_manual_zerocopy(segments, BLOCK_SIZE),
+    );
+    bench_merge(
+        &mut group,
+        "manual_zerocopy_v4",
+        &manual_v4_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                manual_zerocopy_v4::deserialize,
+                manual_zerocopy_v4::serialize,
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "manual_bitpacked",
+        &manual_bitpacked_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                manual_bitpacked::deserialize,
+                manual_bitpacked::serialize,
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "manual_elias_fano",
+        &manual_elias_fano_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                manual_elias_fano::deserialize,
+                |block| manual_elias_fano::serialize(block).unwrap(),
+            )
+        },
+    );
+    bench_merge(
+        &mut group,
+        "manual_roaring",
+        &manual_roaring_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                manual_roaring::deserialize,
+                manual_roaring::serialize,
+            )
+        },
+    );
+    // Codec-to-codec: compact bincode segments into the manual layout
+    bench_merge(
+        &mut group,
+        "bincode_to_manual_zerocopy",
+        &bincode_segments,
+        &live_docs,
+        |segments| {
+            merge::merge(
+                segments,
+                BLOCK_SIZE,
+                |bytes| {
+                    bincode::decode_from_slice(bytes, bincode::config::standard())
+                        .map(|(block, _len)| block)
+                        .map_err(|_| "Invalid bincode block")
+                },
+                manual_zerocopy::serialize,
+            )
+        },
+    );
 
-                    for archived_term in reader.iter() {
-                        let field_mask = archived_term.field_mask();
+    group.finish();
+}
 
-                        if field_mask & query_mask != 0 {
-                            let _doc_id = archived_term.doc_id();
-                            let frequency = archived_term.frequency();
-                            total_frequency += frequency;
-                            matched_count += 1;
-                        }
-                    }
-                }
+fn benchmark_capnp_reader_options(c: &mut Criterion) {
+    let test_data = generate_test_data();
+    let terms_per_block = test_data[0].full_terms.len();
 
-                (total_frequency, matched_count)
-            });
-        });
+    let capnp_blocks: Vec<_> = test_data
+        .iter()
+        .map(|block| {
+            let mut message = Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        })
+        .collect();
 
-        group.bench_function("manual_zerocopy_v3", |b| {
+    let mut group = c.benchmark_group("capnp_reader_options");
+
+    for (name, options) in [
+        ("default", ReaderOptions::new()),
+        ("sized", capnp_view::reader_options(terms_per_block, 1)),
+        ("unlimited", capnp_view::unlimited_reader_options()),
+    ] {
+        group.bench_function(name, |b| {
             b.iter(|| {
                 let mut total_frequency = 0u64;
-                let mut matched_count = 0usize;
 
-                for serialized_block in black_box(&manual_v3_blocks) {
-                    // v3 requires full deserialization (no zero-copy reader)
-                    let block = manual_zerocopy_v3::deserialize(serialized_block).unwrap();
+                for serialized_block in black_box(&capnp_blocks) {
+                    let reader = capnp::serialize::read_message_from_flat_slice(
+                        &mut &serialized_block[..],
+                        options,
+                    )
+                    .unwrap();
+                    let block_reader = capnp_view::BlockReader::new(
+                        reader.get_root::<block_capnp::block::Reader>().unwrap(),
+                    )
+                    .unwrap();
 
-                    for term in &block.full_terms {
-                        if term.field_mask & query_mask != 0 {
-                            let _doc_id = term.doc_id;
-                            let _field_mask = term.field_mask;
-                            total_frequency += term.frequency;
-                            matched_count += 1;
-                        }
+                    for term_reader in block_reader.iter() {
+                        let _doc_id = term_reader.doc_id();
+                        let _field_mask = term_reader.field_mask().unwrap();
+                        total_frequency += term_reader.frequency();
                     }
                 }
 
-                (total_frequency, matched_count)
+                total_frequency
             });
         });
-
-        group.finish();
     }
+
+    group.finish();
 }
 
 fn all_benchmarks(c: &mut Criterion) {
     measure_sizes();
+    measure_compressed_sizes();
+    measure_low_cardinality_sizes();
     benchmark_serialize(c);
+    benchmark_streaming_write(c);
+    benchmark_in_place_update(c);
     benchmark_full_read(c);
     benchmark_filtered_read(c);
+    benchmark_predicates(c);
+    benchmark_capnp_reader_options(c);
+    benchmark_compressed_read(c);
+    benchmark_doc_id_seek(c);
+    benchmark_doc_id_range(c);
+    benchmark_low_cardinality_filtered_read(c);
+    benchmark_clustered_filtered_read(c);
+    benchmark_postings(c);
+    benchmark_top_k(c);
+    benchmark_segment_merge(c);
 }
 
 criterion_group!(benches, all_benchmarks);
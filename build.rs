@@ -1,7 +1,24 @@
+use std::path::Path;
+
 fn main() {
+    println!("cargo:rerun-if-changed=schema/block.capnp");
+    println!("cargo:rerun-if-changed=schema/block_flat.capnp");
     capnpc::CompilerCommand::new()
         .src_prefix("schema")
         .file("schema/block.capnp")
+        .file("schema/block_flat.capnp")
         .run()
         .expect("compiling schema");
+
+    prost_build::compile_protos(&["schema/block.proto"], &["schema"])
+        .expect("compiling proto schema");
+
+    println!("cargo:rerun-if-changed=schema/block.fbs");
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    flatc_rust::run(flatc_rust::Args {
+        inputs: &[Path::new("schema/block.fbs")],
+        out_dir: Path::new(&out_dir),
+        ..Default::default()
+    })
+    .expect("compiling flatbuffers schema");
 }
@@ -74,6 +74,8 @@
             pkgs.rust-analyzer
             pkgs.cargo-watch
             pkgs.capnproto
+            pkgs.protobuf
+            pkgs.flatbuffers
           ];
         };
       }
@@ -0,0 +1,240 @@
+//! Optional per-block summary header for skipping whole blocks
+//!
+//! A [`BlockSummary`] records the OR of every term's field mask, the doc id
+//! range and the highest frequency in a block. A filtered scan can check the
+//! summary first and skip the block without visiting any term when
+//! [`BlockSummary::may_match`] is false; the doc id range and max frequency
+//! serve seeks and top-k queries the same way.
+//!
+//! The summary is kept out of the existing layouts so they stay comparable.
+//! Manual formats carry it as a fixed-size prefix ([`serialize_with_summary`],
+//! [`BlockSummary::read`]) in front of an unchanged block; Cap'n Proto wraps
+//! the unchanged `Block` struct in a `SummarizedBlock` next to its summary
+//! ([`Block::to_capnp_with_summary`], [`BlockSummary::from_capnp`]).
+//!
+//! Prefix layout (all little-endian): `u128` field mask union, `u64` min doc
+//! id, `u64` max doc id, `u64` max frequency.
+
+use crate::predicate::{MaskCondition, Predicate};
+use crate::{block_capnp, Block};
+
+/// Size of the summary prefix in bytes
+pub const SUMMARY_SIZE: usize = 40; // 16 + 8 + 8 + 8
+
+/// Summary of the terms in one block
+///
+/// An empty block has an empty mask union and an empty doc id range
+/// (`min_doc_id > max_doc_id`), so it never matches anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSummary {
+    pub field_mask_union: u128,
+    pub min_doc_id: u64,
+    pub max_doc_id: u64,
+    pub max_frequency: u64,
+}
+
+impl BlockSummary {
+    pub fn new(block: &Block) -> Self {
+        block.full_terms.iter().fold(
+            BlockSummary {
+                field_mask_union: 0,
+                min_doc_id: u64::MAX,
+                max_doc_id: 0,
+                max_frequency: 0,
+            },
+            |summary, term| BlockSummary {
+                field_mask_union: summary.field_mask_union | term.field_mask,
+                min_doc_id: summary.min_doc_id.min(term.doc_id),
+                max_doc_id: summary.max_doc_id.max(term.doc_id),
+                max_frequency: summary.max_frequency.max(term.frequency),
+            },
+        )
+    }
+
+    /// Whether any term in the block can match `query_mask`
+    #[inline(always)]
+    pub fn may_match(&self, query_mask: u128) -> bool {
+        self.field_mask_union & query_mask != 0
+    }
+
+    /// Whether any term in the block can match `predicate`
+    ///
+    /// The union can't rule out `NoneOf`, so only the frequency bound applies
+    /// to it.
+    pub fn may_match_predicate(&self, predicate: &Predicate) -> bool {
+        let mask_may_match = match predicate.mask {
+            MaskCondition::Always | MaskCondition::NoneOf(_) => self.min_doc_id <= self.max_doc_id,
+            MaskCondition::Any(query_mask) => self.may_match(query_mask),
+            MaskCondition::All(query_mask) => {
+                self.min_doc_id <= self.max_doc_id
+                    && self.field_mask_union & query_mask == query_mask
+            }
+        };
+
+        mask_may_match && self.max_frequency >= predicate.min_frequency
+    }
+
+    /// Whether `doc_id` falls inside the block's doc id range
+    #[inline(always)]
+    pub fn may_contain(&self, doc_id: u64) -> bool {
+        self.min_doc_id <= doc_id && doc_id <= self.max_doc_id
+    }
+
+    /// Append the summary prefix to `bytes`
+    pub fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.field_mask_union.to_le_bytes());
+        bytes.extend_from_slice(&self.min_doc_id.to_le_bytes());
+        bytes.extend_from_slice(&self.max_doc_id.to_le_bytes());
+        bytes.extend_from_slice(&self.max_frequency.to_le_bytes());
+    }
+
+    /// Read the summary prefix from the start of `bytes`, returning it
+    /// together with the block bytes that follow it
+    pub fn read(bytes: &[u8]) -> Result<(Self, &[u8]), &'static str> {
+        if bytes.len() < SUMMARY_SIZE {
+            return Err("Buffer too small for summary");
+        }
+
+        let summary = BlockSummary {
+            field_mask_union: u128::from_le_bytes(bytes[0..16].try_into().unwrap()),
+            min_doc_id: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            max_doc_id: u64::from_le_bytes(bytes[24..32].try_into().unwrap()),
+            max_frequency: u64::from_le_bytes(bytes[32..40].try_into().unwrap()),
+        };
+
+        Ok((summary, &bytes[SUMMARY_SIZE..]))
+    }
+
+    /// Fill a Cap'n Proto summary struct
+    pub fn to_capnp(&self, mut builder: block_capnp::block_summary::Builder) {
+        let mut mask_builder = builder.reborrow().init_field_mask_union();
+        mask_builder.set_high((self.field_mask_union >> 64) as u64);
+        mask_builder.set_low(self.field_mask_union as u64);
+
+        builder.set_min_doc_id(self.min_doc_id);
+        builder.set_max_doc_id(self.max_doc_id);
+        builder.set_max_frequency(self.max_frequency);
+    }
+
+    /// Read the summary of a Cap'n Proto `SummarizedBlock`
+    pub fn from_capnp(reader: block_capnp::summarized_block::Reader) -> capnp::Result<Self> {
+        let summary_reader = reader.get_summary()?;
+        let mask_reader = summary_reader.get_field_mask_union()?;
+
+        Ok(BlockSummary {
+            field_mask_union: ((mask_reader.get_high() as u128) << 64)
+                | (mask_reader.get_low() as u128),
+            min_doc_id: summary_reader.get_min_doc_id(),
+            max_doc_id: summary_reader.get_max_doc_id(),
+            max_frequency: summary_reader.get_max_frequency(),
+        })
+    }
+}
+
+/// Serialize a block with `serialize` behind a summary prefix
+pub fn serialize_with_summary(block: &Block, serialize: impl FnOnce(&Block) -> Vec<u8>) -> Vec<u8> {
+    let body = serialize(block);
+
+    let mut bytes = Vec::with_capacity(SUMMARY_SIZE + body.len());
+    BlockSummary::new(block).write(&mut bytes);
+    bytes.extend_from_slice(&body);
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{manual_zerocopy, FullTerm};
+
+    fn test_block() -> Block {
+        Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 10,
+                    field_mask: 0b0001,
+                    frequency: 42,
+                },
+                FullTerm {
+                    doc_id: 12,
+                    field_mask: 1 << 100,
+                    frequency: 123,
+                },
+                FullTerm {
+                    doc_id: 20,
+                    field_mask: 0b0100,
+                    frequency: 7,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = BlockSummary::new(&test_block());
+
+        assert_eq!(summary.field_mask_union, (1 << 100) | 0b0101);
+        assert_eq!(summary.min_doc_id, 10);
+        assert_eq!(summary.max_doc_id, 20);
+        assert_eq!(summary.max_frequency, 123);
+
+        assert!(summary.may_match(0b0100));
+        assert!(!summary.may_match(0b1010));
+        assert!(summary.may_contain(15));
+        assert!(!summary.may_contain(21));
+
+        assert!(summary.may_match_predicate(&Predicate::all(0b0101)));
+        assert!(!summary.may_match_predicate(&Predicate::all(0b0111)));
+        assert!(summary.may_match_predicate(&Predicate::none(0b0101)));
+        assert!(!summary.may_match_predicate(&Predicate::ALWAYS.with_min_frequency(124)));
+
+        let empty = BlockSummary::new(&Block { full_terms: vec![] });
+        assert!(!empty.may_match(!0));
+        assert!(!empty.may_match_predicate(&Predicate::ALWAYS));
+        assert!(!empty.may_contain(0));
+    }
+
+    #[test]
+    fn test_prefix_roundtrip() {
+        let block = test_block();
+
+        let bytes = serialize_with_summary(&block, manual_zerocopy::serialize);
+        let (summary, body) = BlockSummary::read(&bytes).unwrap();
+
+        assert_eq!(summary, BlockSummary::new(&block));
+        assert_eq!(body, manual_zerocopy::serialize(&block));
+        assert!(BlockSummary::read(&bytes[..SUMMARY_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_capnp_roundtrip() {
+        let block = test_block();
+
+        let mut message = capnp::message::Builder::new_default();
+        block.to_capnp_with_summary(&mut message);
+        let reader = message
+            .get_root_as_reader::<block_capnp::summarized_block::Reader>()
+            .unwrap();
+        assert_eq!(
+            BlockSummary::from_capnp(reader).unwrap(),
+            BlockSummary::new(&block)
+        );
+        assert_eq!(
+            Block::from_capnp(reader.get_block().unwrap())
+                .unwrap()
+                .full_terms
+                .len(),
+            3
+        );
+
+        // The wrapped block is the plain block, byte for byte
+        let mut plain = capnp::message::Builder::new_default();
+        block.to_capnp(&mut plain);
+        let mut copy = capnp::message::Builder::new_default();
+        copy.set_root(reader.get_block().unwrap()).unwrap();
+        assert_eq!(
+            capnp::serialize::write_message_to_words(&copy),
+            capnp::serialize::write_message_to_words(&plain)
+        );
+    }
+}
@@ -0,0 +1,491 @@
+//! Zero-copy view over a Cap'n Proto block
+//!
+//! Wraps a `block_capnp::block::Reader` with the same accessor API as the
+//! manual zero-copy readers, so the `u128` field mask is reassembled from its
+//! `high`/`low` halves in one place instead of at every call site.
+//!
+//! Reads go through capnp's traversal limit, which by default rejects messages
+//! that touch more than 64 MiB of words. [`reader_options`] sizes the limit
+//! for a given block size and [`unlimited_reader_options`] disables the
+//! accounting altogether.
+//!
+//! [`BlockWriter`] builds a block term by term without knowing the number of
+//! terms up front.
+
+use std::ops::Range;
+
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
+
+use crate::{block_capnp, partition_point, FullTerm};
+
+/// Words counted against the traversal limit per term: the 3-word term struct,
+/// the 2-word `UInt128` mask and a far-pointer landing pad for masks that end
+/// up in a different segment than the term list
+const TRAVERSAL_WORDS_PER_TERM: usize = 6;
+
+/// Words counted against the traversal limit per block: the root pointer and
+/// struct, the list tag, plus slack for landing pads at segment boundaries
+const TRAVERSAL_WORDS_PER_BLOCK: usize = 8;
+
+/// Number of words a single pass over a block of `num_terms` terms counts
+/// against the traversal limit
+///
+/// This also bounds the message size, which the flat-slice reader checks
+/// against the limit before any traversal happens.
+pub fn traversal_words(num_terms: usize) -> usize {
+    TRAVERSAL_WORDS_PER_BLOCK + num_terms * TRAVERSAL_WORDS_PER_TERM
+}
+
+/// Reader options whose traversal limit allows `passes` full passes over a
+/// block of `num_terms` terms
+///
+/// Every call to `field_mask()` re-reads the mask struct, so a caller that
+/// reads each term's mask twice needs two passes.
+pub fn reader_options(num_terms: usize, passes: usize) -> ReaderOptions {
+    let mut options = ReaderOptions::new();
+    options.traversal_limit_in_words(Some(traversal_words(num_terms) * passes));
+    options
+}
+
+/// Reader options with traversal-limit accounting disabled
+///
+/// Only use this for trusted input: the limit is what protects readers from
+/// messages whose pointers alias the same data many times over.
+pub fn unlimited_reader_options() -> ReaderOptions {
+    let mut options = ReaderOptions::new();
+    options.traversal_limit_in_words(None);
+    options
+}
+
+/// Terms in a [`BlockWriter`]'s first staging chunk; every further chunk
+/// doubles, up to `FIRST_CHUNK_TERMS << MAX_CHUNK_SHIFT`
+const FIRST_CHUNK_TERMS: u32 = 64;
+const MAX_CHUNK_SHIFT: usize = 14;
+
+/// Streaming writer that builds a block term by term, without materializing a
+/// [`crate::Block`] or knowing the number of terms up front
+///
+/// Cap'n Proto fixes a list's length when it is allocated, and the Rust
+/// runtime has no orphans to build a list aside and adopt it later. The writer
+/// therefore stages terms in scratch messages whose term lists grow in
+/// doubling chunks, and [`BlockWriter::finish`] allocates the block's list
+/// with the final count and copies the terms over.
+pub struct BlockWriter<'a> {
+    message: &'a mut Builder<HeapAllocator>,
+    /// Scratch messages, each with a term list as its root
+    chunks: Vec<Builder<HeapAllocator>>,
+    /// Terms written to the last chunk
+    chunk_len: u32,
+    num_terms: usize,
+}
+
+impl<'a> BlockWriter<'a> {
+    /// Start a block that [`BlockWriter::finish`] writes as the root of
+    /// `message`
+    pub fn new(message: &'a mut Builder<HeapAllocator>) -> Self {
+        BlockWriter {
+            message,
+            chunks: Vec::new(),
+            chunk_len: 0,
+            num_terms: 0,
+        }
+    }
+
+    /// Number of terms written so far
+    pub fn len(&self) -> usize {
+        self.num_terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    fn chunk_capacity(index: usize) -> u32 {
+        FIRST_CHUNK_TERMS << index.min(MAX_CHUNK_SHIFT)
+    }
+
+    /// Write the next term
+    #[inline]
+    pub fn push(&mut self, term: &FullTerm) -> Result<(), &'static str> {
+        if self.chunks.is_empty() || self.chunk_len == Self::chunk_capacity(self.chunks.len() - 1) {
+            let mut chunk = Builder::new_default();
+            chunk.initn_root::<capnp::struct_list::Builder<block_capnp::full_term::Owned>>(
+                Self::chunk_capacity(self.chunks.len()),
+            );
+            self.chunks.push(chunk);
+            self.chunk_len = 0;
+        }
+
+        // Just pushed if there was none
+        let terms = self
+            .chunks
+            .last_mut()
+            .unwrap()
+            .get_root::<capnp::struct_list::Builder<block_capnp::full_term::Owned>>()
+            .map_err(|_| "Invalid staging chunk")?;
+
+        let mut term_builder = terms.get(self.chunk_len);
+        term_builder.set_doc_id(term.doc_id);
+
+        let mut mask_builder = term_builder.reborrow().init_field_mask();
+        mask_builder.set_high((term.field_mask >> 64) as u64);
+        mask_builder.set_low(term.field_mask as u64);
+
+        term_builder.set_frequency(term.frequency);
+
+        self.chunk_len += 1;
+        self.num_terms += 1;
+
+        Ok(())
+    }
+
+    /// Allocate the block's term list and copy the staged terms into it
+    pub fn finish(self) -> Result<(), &'static str> {
+        let num_terms =
+            u32::try_from(self.num_terms).map_err(|_| "Too many terms for a capnp list")?;
+        let mut terms = self
+            .message
+            .init_root::<block_capnp::block::Builder>()
+            .init_full_terms(num_terms);
+
+        let mut index = 0;
+        let last_chunk = self.chunks.len().saturating_sub(1);
+        for (chunk_index, chunk) in self.chunks.iter().enumerate() {
+            let chunk_terms = chunk
+                .get_root_as_reader::<capnp::struct_list::Reader<block_capnp::full_term::Owned>>()
+                .map_err(|_| "Invalid staging chunk")?;
+            let len = if chunk_index == last_chunk {
+                self.chunk_len
+            } else {
+                chunk_terms.len()
+            };
+
+            for term in chunk_terms.iter().take(len as usize) {
+                terms
+                    .set_with_caveats(index, term)
+                    .map_err(|_| "Invalid staged term")?;
+                index += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Zero-copy reader for accessing a capnp block without full deserialization
+pub struct BlockReader<'a> {
+    terms: capnp::struct_list::Reader<'a, block_capnp::full_term::Owned>,
+}
+
+impl<'a> BlockReader<'a> {
+    pub fn new(reader: block_capnp::block::Reader<'a>) -> capnp::Result<Self> {
+        let terms = reader.get_full_terms()?;
+
+        Ok(BlockReader { terms })
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Read the term at `index` without iterating up to it
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<TermReader<'a>> {
+        (index < self.len()).then(|| TermReader {
+            reader: self.terms.get(index as u32),
+        })
+    }
+
+    pub fn iter(&self) -> TermIterator<'a> {
+        TermIterator {
+            terms: self.terms,
+            index: 0,
+            end: self.terms.len(),
+        }
+    }
+
+    /// Iterate over the terms with a doc_id in `doc_ids`, binary-searching for
+    /// the bounds
+    ///
+    /// Doc ids must be sorted in ascending order, as they are in every block.
+    pub fn range(&self, doc_ids: Range<u64>) -> TermIterator<'a> {
+        let doc_id = |index| self.terms.get(index as u32).get_doc_id();
+        let start = partition_point(self.len(), |index| doc_id(index) < doc_ids.start);
+        let end = partition_point(self.len(), |index| doc_id(index) < doc_ids.end);
+
+        TermIterator {
+            terms: self.terms,
+            index: start as u32,
+            end: end.max(start) as u32,
+        }
+    }
+}
+
+/// Iterator over terms in a capnp block (zero-copy)
+pub struct TermIterator<'a> {
+    terms: capnp::struct_list::Reader<'a, block_capnp::full_term::Owned>,
+    index: u32,
+    end: u32,
+}
+
+impl<'a> Iterator for TermIterator<'a> {
+    type Item = TermReader<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+
+        let term = TermReader {
+            reader: self.terms.get(self.index),
+        };
+
+        self.index += 1;
+
+        Some(term)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for TermIterator<'a> {}
+
+/// Zero-copy reader for a single capnp term
+pub struct TermReader<'a> {
+    reader: block_capnp::full_term::Reader<'a>,
+}
+
+impl<'a> TermReader<'a> {
+    /// Read doc_id without deserializing the entire term
+    #[inline(always)]
+    pub fn doc_id(&self) -> u64 {
+        self.reader.get_doc_id()
+    }
+
+    /// Read field_mask without deserializing the entire term
+    ///
+    /// Fails if the mask pointer is invalid or following it exceeds the
+    /// traversal limit.
+    #[inline(always)]
+    pub fn field_mask(&self) -> capnp::Result<u128> {
+        let mask_reader = self.reader.get_field_mask()?;
+
+        Ok(((mask_reader.get_high() as u128) << 64) | (mask_reader.get_low() as u128))
+    }
+
+    /// Read frequency without deserializing the entire term
+    #[inline(always)]
+    pub fn frequency(&self) -> u64 {
+        self.reader.get_frequency()
+    }
+
+    /// Fully deserialize this term
+    pub fn deserialize(&self) -> capnp::Result<FullTerm> {
+        Ok(FullTerm {
+            doc_id: self.doc_id(),
+            field_mask: self.field_mask()?,
+            frequency: self.frequency(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Block;
+    use capnp::message::{Builder, ReaderOptions};
+
+    #[test]
+    fn test_zero_copy_reader() {
+        let block = Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 100,
+                    field_mask: (0xFF00FF00 << 64) | 0xDEADBEEF,
+                    frequency: 7,
+                },
+                FullTerm {
+                    doc_id: 101,
+                    field_mask: 0xCAFEBABE,
+                    frequency: 9,
+                },
+            ],
+        };
+
+        let mut message = Builder::new_default();
+        block.to_capnp(&mut message);
+        let bytes = capnp::serialize::write_message_to_words(&message);
+
+        let message_reader =
+            capnp::serialize::read_message_from_flat_slice(&mut &bytes[..], ReaderOptions::new())
+                .unwrap();
+        let reader = BlockReader::new(
+            message_reader
+                .get_root::<block_capnp::block::Reader>()
+                .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(reader.len(), 2);
+
+        let mut terms = reader.iter();
+        assert_eq!(terms.len(), 2);
+
+        let term = terms.next().unwrap();
+        assert_eq!(term.doc_id(), 100);
+        assert_eq!(term.field_mask().unwrap(), (0xFF00FF00 << 64) | 0xDEADBEEF);
+        assert_eq!(term.frequency(), 7);
+
+        let term = terms.next().unwrap().deserialize().unwrap();
+        assert_eq!(term.doc_id, 101);
+        assert_eq!(term.field_mask, 0xCAFEBABE);
+        assert_eq!(term.frequency, 9);
+
+        assert!(terms.next().is_none());
+    }
+
+    // Large enough that a full read exceeds capnp's default traversal limit
+    const LARGE_BLOCK_TERMS: usize = 1 << 21;
+
+    fn large_block_bytes() -> Vec<u8> {
+        let block = Block {
+            full_terms: (0..LARGE_BLOCK_TERMS as u64)
+                .map(|i| FullTerm {
+                    doc_id: i,
+                    field_mask: (i as u128) << 64 | 1,
+                    frequency: i % 1000 + 1,
+                })
+                .collect(),
+        };
+
+        let mut message = Builder::new_default();
+        block.to_capnp(&mut message);
+        capnp::serialize::write_message_to_words(&message)
+    }
+
+    fn sum_block(bytes: &[u8], options: ReaderOptions) -> capnp::Result<(u64, usize)> {
+        let message_reader =
+            capnp::serialize::read_message_from_flat_slice(&mut &bytes[..], options)?;
+        let reader = BlockReader::new(message_reader.get_root::<block_capnp::block::Reader>()?)?;
+
+        let mut total_frequency = 0u64;
+        let mut num_terms = 0usize;
+        for term in reader.iter() {
+            term.field_mask()?;
+            total_frequency += term.frequency();
+            num_terms += 1;
+        }
+
+        Ok((total_frequency, num_terms))
+    }
+
+    #[test]
+    fn test_large_block_default_options() {
+        let bytes = large_block_bytes();
+
+        assert!(sum_block(&bytes, ReaderOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_large_block_sized_options() {
+        let bytes = large_block_bytes();
+        let expected: u64 = (0..LARGE_BLOCK_TERMS as u64).map(|i| i % 1000 + 1).sum();
+
+        let (total_frequency, num_terms) =
+            sum_block(&bytes, reader_options(LARGE_BLOCK_TERMS, 1)).unwrap();
+        assert_eq!(num_terms, LARGE_BLOCK_TERMS);
+        assert_eq!(total_frequency, expected);
+
+        let (total_frequency, num_terms) = sum_block(&bytes, unlimited_reader_options()).unwrap();
+        assert_eq!(num_terms, LARGE_BLOCK_TERMS);
+        assert_eq!(total_frequency, expected);
+    }
+
+    #[test]
+    fn test_range() {
+        let block = Block {
+            full_terms: [2, 4, 4, 7, 9]
+                .into_iter()
+                .map(|doc_id| FullTerm {
+                    doc_id,
+                    field_mask: 1,
+                    frequency: doc_id * 10,
+                })
+                .collect(),
+        };
+
+        let mut message = Builder::new_default();
+        block.to_capnp(&mut message);
+        let reader = BlockReader::new(
+            message
+                .get_root_as_reader::<block_capnp::block::Reader>()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let ranges: [(u64, u64, &[u64]); 4] =
+            [(0, 3, &[2]), (4, 5, &[4, 4]), (5, 7, &[]), (9, 2, &[])];
+        for (start, end, expected) in ranges {
+            let terms = reader.range(start..end);
+            assert_eq!(terms.len(), expected.len());
+            let doc_ids: Vec<u64> = terms.map(|term| term.doc_id()).collect();
+            assert_eq!(doc_ids, expected, "range {start}..{end}");
+        }
+    }
+
+    #[test]
+    fn test_block_writer() {
+        let block = Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 5,
+                    field_mask: (1 << 100) | 3,
+                    frequency: 1,
+                },
+                FullTerm {
+                    doc_id: 8,
+                    field_mask: 0,
+                    frequency: 2,
+                },
+            ],
+        };
+
+        // Spans several staging chunks
+        let large = crate::generate_test_data()
+            .into_iter()
+            .take(3)
+            .flat_map(|block| block.full_terms)
+            .collect();
+
+        for block in [
+            block,
+            Block {
+                full_terms: Vec::new(),
+            },
+            Block { full_terms: large },
+        ] {
+            let mut message = Builder::new_default();
+            let mut writer = BlockWriter::new(&mut message);
+            for term in &block.full_terms {
+                writer.push(term).unwrap();
+            }
+            assert_eq!(writer.len(), block.full_terms.len());
+            writer.finish().unwrap();
+
+            let mut expected = Builder::new_default();
+            block.to_capnp(&mut expected);
+            assert_eq!(
+                capnp::serialize::write_message_to_words(&message),
+                capnp::serialize::write_message_to_words(&expected)
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Block compression layered over any codec
+//!
+//! Works on the serialized bytes of a block, so it composes with every codec
+//! in the crate. Both formats record the uncompressed length up front (LZ4 as
+//! a u32 little-endian prefix, Zstd in its frame header), so readers can size
+//! their output buffer before decompressing. That length comes from untrusted
+//! input, so it is checked against [`MAX_DECOMPRESSED_LEN`] (and, for LZ4,
+//! against the most the format can expand its input) before anything is
+//! allocated.
+
+/// Largest uncompressed block a [`Decompressor`] accepts
+pub const MAX_DECOMPRESSED_LEN: usize = 64 << 20; // 64 MiB
+
+/// LZ4 encodes at most 255 bytes of match length per input byte
+const LZ4_MAX_RATIO: usize = 255;
+
+/// Compression algorithm and level applied to serialized blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// LZ4 block compression; level 0 uses the default fast mode and higher
+    /// levels use LZ4 HC
+    Lz4 { level: i32 },
+    /// Zstd compression at the given level
+    Zstd { level: i32 },
+}
+
+impl Compression {
+    /// Short name used in benchmark and size reports
+    pub fn name(&self) -> String {
+        match self {
+            Compression::Lz4 { level: 0 } => "lz4".to_string(),
+            Compression::Lz4 { level } => format!("lz4_hc{level}"),
+            Compression::Zstd { level } => format!("zstd{level}"),
+        }
+    }
+
+    /// Compress the serialized bytes of a block
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match *self {
+            Compression::Lz4 { level } => {
+                let mode = if level == 0 {
+                    lz4::block::CompressionMode::DEFAULT
+                } else {
+                    lz4::block::CompressionMode::HIGHCOMPRESSION(level)
+                };
+
+                lz4::block::compress(bytes, Some(mode), true).map_err(|_| "LZ4 compression failed")
+            }
+            Compression::Zstd { level } => {
+                zstd::bulk::compress(bytes, level).map_err(|_| "Zstd compression failed")
+            }
+        }
+    }
+
+    /// Create a reusable decompressor for blocks compressed with this setting
+    pub fn decompressor(&self) -> Result<Decompressor, &'static str> {
+        let zstd = match self {
+            Compression::Lz4 { .. } => None,
+            Compression::Zstd { .. } => {
+                Some(zstd::bulk::Decompressor::new().map_err(|_| "Failed to create Zstd context")?)
+            }
+        };
+
+        Ok(Decompressor { zstd })
+    }
+}
+
+/// Decompressor holding any per-stream state (the Zstd context) so it can be
+/// reused across blocks
+pub struct Decompressor {
+    zstd: Option<zstd::bulk::Decompressor<'static>>,
+}
+
+impl Decompressor {
+    /// Uncompressed length of a compressed block, rejecting lengths above
+    /// [`MAX_DECOMPRESSED_LEN`] or beyond what the compressed bytes can hold
+    pub fn decompressed_len(&self, bytes: &[u8]) -> Result<usize, &'static str> {
+        let len = match self.zstd {
+            None => {
+                if bytes.len() < 4 {
+                    return Err("Buffer too small for header");
+                }
+
+                let len = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+                if len > (bytes.len() - 4) * LZ4_MAX_RATIO {
+                    return Err("LZ4 length prefix exceeds the compressed data");
+                }
+
+                len
+            }
+            Some(_) => match zstd::zstd_safe::get_frame_content_size(bytes) {
+                Ok(Some(len)) => usize::try_from(len).unwrap_or(usize::MAX),
+                Ok(None) => return Err("Zstd frame has no content size"),
+                Err(_) => return Err("Invalid Zstd frame header"),
+            },
+        };
+
+        if len > MAX_DECOMPRESSED_LEN {
+            return Err("Decompressed length exceeds the maximum block size");
+        }
+
+        Ok(len)
+    }
+
+    /// Decompress a block into `out`, which must hold at least
+    /// [`Self::decompressed_len`] bytes. Returns the number of bytes written.
+    pub fn decompress_to_slice(
+        &mut self,
+        bytes: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, &'static str> {
+        match &mut self.zstd {
+            None => lz4::block::decompress_to_buffer(bytes, None, out)
+                .map_err(|_| "LZ4 decompression failed"),
+            Some(zstd) => zstd
+                .decompress_to_buffer(bytes, out)
+                .map_err(|_| "Zstd decompression failed"),
+        }
+    }
+
+    /// Decompress a block into `out`, replacing its contents
+    pub fn decompress_into(&mut self, bytes: &[u8], out: &mut Vec<u8>) -> Result<(), &'static str> {
+        let len = self.decompressed_len(bytes)?;
+
+        out.clear();
+        out.resize(len, 0);
+        let written = self.decompress_to_slice(bytes, out)?;
+        out.truncate(written);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_test_data, manual_zerocopy};
+
+    const COMPRESSIONS: [Compression; 4] = [
+        Compression::Lz4 { level: 0 },
+        Compression::Lz4 { level: 9 },
+        Compression::Zstd { level: 1 },
+        Compression::Zstd { level: 19 },
+    ];
+
+    #[test]
+    fn test_roundtrip() {
+        let block = &generate_test_data()[0];
+        let bytes = manual_zerocopy::serialize(block);
+
+        for compression in COMPRESSIONS {
+            let compressed = compression.compress(&bytes).unwrap();
+            let mut decompressor = compression.decompressor().unwrap();
+
+            assert_eq!(decompressor.decompressed_len(&compressed), Ok(bytes.len()));
+
+            let mut out = Vec::new();
+            decompressor.decompress_into(&compressed, &mut out).unwrap();
+            assert_eq!(out, bytes, "{}", compression.name());
+        }
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        for compression in COMPRESSIONS {
+            let compressed = compression.compress(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+            let mut decompressor = compression.decompressor().unwrap();
+
+            let mut out = Vec::new();
+            assert!(decompressor
+                .decompress_into(&compressed[..2], &mut out)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_oversized_length() {
+        let bytes = manual_zerocopy::serialize(&generate_test_data()[0]);
+
+        // A corrupt LZ4 prefix claiming far more than the payload can expand to
+        let mut compressed = Compression::Lz4 { level: 0 }.compress(&bytes).unwrap();
+        compressed[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let decompressor = Compression::Lz4 { level: 0 }.decompressor().unwrap();
+        assert!(decompressor.decompressed_len(&compressed).is_err());
+
+        // A valid Zstd frame whose content exceeds the cap
+        let compression = Compression::Zstd { level: 1 };
+        let compressed = compression
+            .compress(&vec![0; MAX_DECOMPRESSED_LEN + 1])
+            .unwrap();
+        let mut decompressor = compression.decompressor().unwrap();
+        assert!(decompressor.decompressed_len(&compressed).is_err());
+        assert!(decompressor
+            .decompress_into(&compressed, &mut Vec::new())
+            .is_err());
+    }
+}
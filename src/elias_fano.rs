@@ -0,0 +1,435 @@
+//! Elias–Fano encoding for non-decreasing `u64` sequences
+//!
+//! Each value (relative to the first one) is split into `low_bits` low bits,
+//! which are bit-packed as-is, and the remaining high bits, which are stored
+//! in unary in a bitvector: value `i` sets bit `(value >> low_bits) + i`.
+//! Choosing `low_bits = floor(log2(universe / n))` keeps the column at about
+//! `2 + log2(universe / n)` bits per value, and both random access ([`get`])
+//! and successor queries ([`next_geq`]) work without decoding the column.
+//!
+//! Layout (all little-endian):
+//! - header: `u32` value count, `u64` first value, `u8` low bit count,
+//!   `u32` high bitvector length in bits
+//! - high bitvector as `u64` words
+//! - packed low bits
+//!
+//! [`EliasFano::new`] checks that the high bitvector has exactly one set bit
+//! per value and none past its length, so every value's high part can be
+//! rebuilt from its bit position. Values add the first value with wrapping
+//! arithmetic, so a corrupt header yields wrong values instead of a panic.
+//!
+//! [`get`]: EliasFano::get
+//! [`next_geq`]: EliasFano::next_geq
+
+use crate::manual_bitpacked::{pack, packed_len, unpack};
+
+const HEADER_SIZE: usize = 17; // 4 + 8 + 1 + 4
+const WORD_SIZE: usize = 8;
+
+/// Append the Elias–Fano encoding of `values` to `bytes`
+///
+/// Fails if `values` is not sorted in non-decreasing order.
+pub fn encode(values: &[u64], bytes: &mut Vec<u8>) -> Result<(), &'static str> {
+    if values.windows(2).any(|pair| pair[0] > pair[1]) {
+        return Err("Values must be non-decreasing");
+    }
+
+    let num_values = values.len();
+    let base = values.first().copied().unwrap_or(0);
+    let max = values.last().map_or(0, |last| last - base);
+
+    // Values are stored relative to `base`, so the universe is `max + 1`
+    let low_bits = match (max as u128 + 1) / num_values.max(1) as u128 {
+        0 => 0,
+        ratio => ratio.ilog2() as u8,
+    };
+
+    let high_len = if num_values == 0 {
+        0
+    } else {
+        num_values + (max >> low_bits) as usize + 1
+    };
+    let mut high = vec![0u64; high_len.div_ceil(64)];
+    for (i, value) in values.iter().enumerate() {
+        let position = ((value - base) >> low_bits) as usize + i;
+        high[position / 64] |= 1 << (position % 64);
+    }
+
+    bytes.extend_from_slice(&(num_values as u32).to_le_bytes());
+    bytes.extend_from_slice(&base.to_le_bytes());
+    bytes.push(low_bits);
+    bytes.extend_from_slice(&(high_len as u32).to_le_bytes());
+
+    for word in &high {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+
+    let low_mask = low_mask(low_bits);
+    pack(
+        values.iter().map(|value| (value - base) & low_mask),
+        low_bits,
+        bytes,
+    );
+
+    Ok(())
+}
+
+#[inline(always)]
+fn low_mask(low_bits: u8) -> u64 {
+    if low_bits == 0 {
+        0
+    } else {
+        u64::MAX >> (64 - low_bits as u32)
+    }
+}
+
+/// Zero-copy reader over an Elias–Fano encoded column
+#[derive(Clone, Copy)]
+pub struct EliasFano<'a> {
+    num_values: usize,
+    base: u64,
+    low_bits: u8,
+    high_len: usize,
+    high: &'a [u8],
+    low: &'a [u8],
+}
+
+impl<'a> EliasFano<'a> {
+    /// Read a column from the start of `bytes`, returning it together with the
+    /// bytes that follow it
+    pub fn new(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), &'static str> {
+        if bytes.len() < HEADER_SIZE {
+            return Err("Buffer too small for header");
+        }
+
+        let num_values = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+        let base = u64::from_le_bytes(bytes[4..12].try_into().unwrap());
+        let low_bits = bytes[12];
+        let high_len = u32::from_le_bytes(bytes[13..17].try_into().unwrap()) as usize;
+
+        if low_bits >= 64 || high_len < num_values {
+            return Err("Invalid Elias-Fano header");
+        }
+
+        let high_end = HEADER_SIZE + high_len.div_ceil(64) * WORD_SIZE;
+        let low_end = high_end + packed_len(num_values, low_bits);
+
+        if bytes.len() < low_end {
+            return Err("Buffer too small for data");
+        }
+
+        let column = EliasFano {
+            num_values,
+            base,
+            low_bits,
+            high_len,
+            high: &bytes[HEADER_SIZE..high_end],
+            low: &bytes[high_end..low_end],
+        };
+
+        let num_words = high_len.div_ceil(64);
+        let ones: usize = (0..num_words)
+            .map(|word_index| column.high_word(word_index).count_ones() as usize)
+            .sum();
+        let padding = if high_len.is_multiple_of(64) {
+            0
+        } else {
+            column.high_word(num_words - 1) >> (high_len % 64)
+        };
+
+        if ones != num_values || padding != 0 {
+            return Err("Invalid Elias-Fano high bits");
+        }
+
+        Ok((column, &bytes[low_end..]))
+    }
+
+    pub fn len(&self) -> usize {
+        self.num_values
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_values == 0
+    }
+
+    #[inline(always)]
+    fn high_word(&self, index: usize) -> u64 {
+        let offset = index * WORD_SIZE;
+        u64::from_le_bytes(self.high[offset..offset + WORD_SIZE].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    fn low(&self, index: usize) -> u64 {
+        unpack(self.low, self.low_bits, index)
+    }
+
+    /// Value `index` given the position of its bit in the high bitvector
+    #[inline(always)]
+    fn value_at(&self, index: usize, position: usize) -> u64 {
+        let high = (position - index) as u64;
+        self.base
+            .wrapping_add((high << self.low_bits) | self.low(index))
+    }
+
+    /// Iterator over the positions of set bits in the high bitvector, starting
+    /// at bit `start`
+    fn ones_from(&self, start: usize) -> Ones<'a> {
+        let word_index = start / 64;
+        let word = if start < self.high_len {
+            self.high_word(word_index) & (u64::MAX << (start % 64))
+        } else {
+            0
+        };
+
+        Ones {
+            column: *self,
+            word_index,
+            word,
+        }
+    }
+
+    /// Position of the `rank`-th unset bit in the high bitvector (0-based)
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        let mut remaining = rank;
+
+        for word_index in 0..self.high_len.div_ceil(64) {
+            let mut zeros = !self.high_word(word_index);
+            let count = zeros.count_ones() as usize;
+
+            if remaining < count {
+                for _ in 0..remaining {
+                    zeros &= zeros - 1;
+                }
+
+                let position = word_index * 64 + zeros.trailing_zeros() as usize;
+                return (position < self.high_len).then_some(position);
+            }
+
+            remaining -= count;
+        }
+
+        None
+    }
+
+    /// Decode the value at `index`
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index >= self.num_values {
+            return None;
+        }
+
+        // Find the `index`-th set bit by skipping whole words first
+        let mut remaining = index;
+        for word_index in 0..self.high_len.div_ceil(64) {
+            let mut word = self.high_word(word_index);
+            let count = word.count_ones() as usize;
+
+            if remaining < count {
+                for _ in 0..remaining {
+                    word &= word - 1;
+                }
+
+                let position = word_index * 64 + word.trailing_zeros() as usize;
+                return Some(self.value_at(index, position));
+            }
+
+            remaining -= count;
+        }
+
+        None
+    }
+
+    /// Find the first value that is greater than or equal to `target`,
+    /// returning its index and the value
+    pub fn next_geq(&self, target: u64) -> Option<(usize, u64)> {
+        if self.num_values == 0 {
+            return None;
+        }
+
+        if target <= self.base {
+            return Some((0, self.base));
+        }
+
+        let relative = target - self.base;
+        let bucket = (relative >> self.low_bits) as usize;
+
+        // Values in high bucket `bucket` start right after the `bucket - 1`-th
+        // zero, and every bit before that position is either a zero or a value
+        // in a lower bucket
+        let start = if bucket == 0 {
+            0
+        } else {
+            self.select_zero(bucket - 1)? + 1
+        };
+        let first_index = start - bucket;
+
+        (first_index..self.num_values)
+            .zip(self.ones_from(start))
+            .map(|(index, position)| (index, self.value_at(index, position)))
+            .find(|&(_, value)| value >= target)
+    }
+
+    /// Iterate over all values in order
+    pub fn iter(&self) -> Iter<'a> {
+        Iter {
+            ones: self.ones_from(0),
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over the set bits of a column's high bitvector
+struct Ones<'a> {
+    column: EliasFano<'a>,
+    word_index: usize,
+    word: u64,
+}
+
+impl<'a> Iterator for Ones<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.word_index += 1;
+
+            if self.word_index * 64 >= self.column.high_len {
+                return None;
+            }
+
+            self.word = self.column.high_word(self.word_index);
+        }
+
+        let position = self.word_index * 64 + self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+
+        Some(position)
+    }
+}
+
+/// Sequential decoder over all values of a column
+pub struct Iter<'a> {
+    ones: Ones<'a>,
+    index: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        if self.index >= self.ones.column.num_values {
+            return None;
+        }
+
+        let position = self.ones.next()?;
+        let value = self.ones.column.value_at(self.index, position);
+        self.index += 1;
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.ones.column.num_values - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(values: &[u64]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        encode(values, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn check(values: &[u64]) {
+        let bytes = encoded(values);
+        let (column, rest) = EliasFano::new(&bytes).unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(column.len(), values.len());
+        assert_eq!(column.iter().collect::<Vec<_>>(), values);
+
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(column.get(i), Some(*value));
+        }
+        assert_eq!(column.get(values.len()), None);
+
+        // Compare next_geq against a linear scan around every value
+        let mut targets = vec![0, u64::MAX];
+        for value in values {
+            targets.extend([value.saturating_sub(1), *value, value.saturating_add(1)]);
+        }
+
+        for target in targets {
+            let expected = values
+                .iter()
+                .position(|value| *value >= target)
+                .map(|i| (i, values[i]));
+            assert_eq!(column.next_geq(target), expected, "target {target}");
+        }
+    }
+
+    #[test]
+    fn test_generated_doc_ids() {
+        let doc_ids: Vec<u64> = crate::generate_test_data()
+            .iter()
+            .take(20)
+            .flat_map(|block| block.full_terms.iter().map(|term| term.doc_id))
+            .collect();
+
+        check(&doc_ids);
+        check(&doc_ids[..100]);
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        check(&[]);
+        check(&[42]);
+        check(&[7, 7, 7, 8]);
+        check(&[0, 1, 2, 3, 4, 5]);
+        check(&[10, 1 << 20, 1 << 40, u64::MAX]);
+        check(&(0..200).map(|i| 1_000_000 + i * 3).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_unsorted_rejected() {
+        let mut bytes = Vec::new();
+        assert!(encode(&[3, 2], &mut bytes).is_err());
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        let bytes = encoded(&[1, 5, 9, 200]);
+
+        assert!(EliasFano::new(&bytes[..4]).is_err());
+        assert!(EliasFano::new(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_high_bits() {
+        let bytes = encoded(&[1, 5, 9, 200]);
+        let high_len = u32::from_le_bytes(bytes[13..17].try_into().unwrap()) as usize;
+
+        // A missing value bit
+        let mut corrupt = bytes.clone();
+        corrupt[HEADER_SIZE] &= !1;
+        assert!(EliasFano::new(&corrupt).is_err());
+
+        // A bit set in the padding past the bitvector's length
+        let mut corrupt = bytes.clone();
+        corrupt[HEADER_SIZE + high_len / 8] |= 1 << (high_len % 8);
+        assert!(EliasFano::new(&corrupt).is_err());
+
+        // A base that overflows reads as garbage, not a panic
+        let mut corrupt = bytes;
+        corrupt[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        let (column, _) = EliasFano::new(&corrupt).unwrap();
+        assert_eq!(column.iter().count(), 4);
+        column.next_geq(u64::MAX);
+    }
+}
@@ -1,6 +1,22 @@
+pub mod block_summary;
+pub mod capnp_view;
+pub mod compression;
+pub mod elias_fano;
+pub mod live_docs;
+pub mod manual_bitpacked;
+pub mod manual_dictionary;
+pub mod manual_elias_fano;
+pub mod manual_roaring;
 pub mod manual_zerocopy;
 pub mod manual_zerocopy_v2;
 pub mod manual_zerocopy_v3;
+pub mod manual_zerocopy_v4;
+pub mod merge;
+pub mod postings;
+pub mod predicate;
+pub mod top_k;
+
+use std::ops::Range;
 
 #[derive(
     rkyv::Archive,
@@ -12,6 +28,12 @@ pub mod manual_zerocopy_v3;
     bincode::Decode,
     serde::Serialize,
     serde::Deserialize,
+    bitcode::Encode,
+    bitcode::Decode,
+    borsh::BorshSerialize,
+    borsh::BorshDeserialize,
+    speedy::Readable,
+    speedy::Writable,
 )]
 #[rkyv(compare(PartialEq), derive(Debug))]
 pub struct FullTerm {
@@ -30,16 +52,42 @@ pub struct FullTerm {
     bincode::Decode,
     serde::Serialize,
     serde::Deserialize,
+    bitcode::Encode,
+    bitcode::Decode,
+    borsh::BorshSerialize,
+    borsh::BorshDeserialize,
+    speedy::Readable,
+    speedy::Writable,
 )]
 #[rkyv(compare(PartialEq), derive(Debug))]
 pub struct Block {
     pub full_terms: Vec<FullTerm>,
 }
 
+impl ArchivedBlock {
+    /// Terms with a doc_id in `doc_ids`, found by binary search over the
+    /// archived terms
+    ///
+    /// Doc ids must be sorted in ascending order, as they are in every block.
+    pub fn range(&self, doc_ids: Range<u64>) -> &[ArchivedFullTerm] {
+        let start = self
+            .full_terms
+            .partition_point(|term| term.doc_id.to_native() < doc_ids.start);
+        let end = self
+            .full_terms
+            .partition_point(|term| term.doc_id.to_native() < doc_ids.end);
+
+        &self.full_terms[start..end.max(start)]
+    }
+}
+
 // Cap'n Proto conversion helpers
 impl Block {
     pub fn to_capnp(&self, builder: &mut capnp::message::Builder<capnp::message::HeapAllocator>) {
-        let mut block_builder = builder.init_root::<block_capnp::block::Builder>();
+        self.fill_capnp(builder.init_root::<block_capnp::block::Builder>());
+    }
+
+    fn fill_capnp(&self, mut block_builder: block_capnp::block::Builder) {
         let mut terms_builder = block_builder
             .reborrow()
             .init_full_terms(self.full_terms.len() as u32);
@@ -56,6 +104,17 @@ impl Block {
         }
     }
 
+    /// Write the block wrapped in a `SummarizedBlock` together with its
+    /// summary
+    pub fn to_capnp_with_summary(
+        &self,
+        builder: &mut capnp::message::Builder<capnp::message::HeapAllocator>,
+    ) {
+        let mut root = builder.init_root::<block_capnp::summarized_block::Builder>();
+        block_summary::BlockSummary::new(self).to_capnp(root.reborrow().init_summary());
+        self.fill_capnp(root.init_block());
+    }
+
     pub fn from_capnp(reader: block_capnp::block::Reader) -> capnp::Result<Self> {
         let terms_reader = reader.get_full_terms()?;
         let mut full_terms = Vec::with_capacity(terms_reader.len() as usize);
@@ -74,6 +133,245 @@ impl Block {
 
         Ok(Block { full_terms })
     }
+
+    pub fn to_capnp_flat(
+        &self,
+        builder: &mut capnp::message::Builder<capnp::message::HeapAllocator>,
+    ) {
+        let mut block_builder = builder.init_root::<block_flat_capnp::block::Builder>();
+        let mut terms_builder = block_builder
+            .reborrow()
+            .init_full_terms(self.full_terms.len() as u32);
+
+        for (i, term) in self.full_terms.iter().enumerate() {
+            let mut term_builder = terms_builder.reborrow().get(i as u32);
+            term_builder.set_doc_id(term.doc_id);
+            term_builder.set_field_mask_high((term.field_mask >> 64) as u64);
+            term_builder.set_field_mask_low(term.field_mask as u64);
+            term_builder.set_frequency(term.frequency);
+        }
+    }
+
+    pub fn from_capnp_flat(reader: block_flat_capnp::block::Reader) -> capnp::Result<Self> {
+        let terms_reader = reader.get_full_terms()?;
+        let mut full_terms = Vec::with_capacity(terms_reader.len() as usize);
+
+        for term_reader in terms_reader.iter() {
+            let field_mask = ((term_reader.get_field_mask_high() as u128) << 64)
+                | (term_reader.get_field_mask_low() as u128);
+
+            full_terms.push(FullTerm {
+                doc_id: term_reader.get_doc_id(),
+                field_mask,
+                frequency: term_reader.get_frequency(),
+            });
+        }
+
+        Ok(Block { full_terms })
+    }
+}
+
+// Protocol Buffers conversion helpers
+impl Block {
+    pub fn to_proto(&self) -> block_proto::Block {
+        block_proto::Block {
+            full_terms: self
+                .full_terms
+                .iter()
+                .map(|term| block_proto::FullTerm {
+                    doc_id: term.doc_id,
+                    field_mask: Some(block_proto::UInt128 {
+                        high: (term.field_mask >> 64) as u64,
+                        low: term.field_mask as u64,
+                    }),
+                    frequency: term.frequency,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_proto(block: &block_proto::Block) -> Self {
+        let full_terms = block
+            .full_terms
+            .iter()
+            .map(|term| {
+                let field_mask = term
+                    .field_mask
+                    .map_or(0, |mask| ((mask.high as u128) << 64) | (mask.low as u128));
+
+                FullTerm {
+                    doc_id: term.doc_id,
+                    field_mask,
+                    frequency: term.frequency,
+                }
+            })
+            .collect();
+
+        Block { full_terms }
+    }
+
+    pub fn to_proto_fixed(&self) -> block_proto::FixedBlock {
+        block_proto::FixedBlock {
+            full_terms: self
+                .full_terms
+                .iter()
+                .map(|term| block_proto::FixedFullTerm {
+                    doc_id: term.doc_id,
+                    field_mask: Some(block_proto::FixedUInt128 {
+                        high: (term.field_mask >> 64) as u64,
+                        low: term.field_mask as u64,
+                    }),
+                    frequency: term.frequency,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_proto_fixed(block: &block_proto::FixedBlock) -> Self {
+        let full_terms = block
+            .full_terms
+            .iter()
+            .map(|term| {
+                let field_mask = term
+                    .field_mask
+                    .map_or(0, |mask| ((mask.high as u128) << 64) | (mask.low as u128));
+
+                FullTerm {
+                    doc_id: term.doc_id,
+                    field_mask,
+                    frequency: term.frequency,
+                }
+            })
+            .collect();
+
+        Block { full_terms }
+    }
+
+    pub fn to_proto_packed(&self) -> block_proto::PackedBlock {
+        let terms = &self.full_terms;
+
+        block_proto::PackedBlock {
+            doc_ids: terms.iter().map(|term| term.doc_id).collect(),
+            field_mask_highs: terms
+                .iter()
+                .map(|term| (term.field_mask >> 64) as u64)
+                .collect(),
+            field_mask_lows: terms.iter().map(|term| term.field_mask as u64).collect(),
+            frequencies: terms.iter().map(|term| term.frequency).collect(),
+        }
+    }
+
+    pub fn from_proto_packed(block: &block_proto::PackedBlock) -> Result<Self, &'static str> {
+        Self::from_columns(
+            &block.doc_ids,
+            &block.field_mask_highs,
+            &block.field_mask_lows,
+            &block.frequencies,
+        )
+    }
+
+    pub fn to_proto_packed_fixed(&self) -> block_proto::PackedFixedBlock {
+        let terms = &self.full_terms;
+
+        block_proto::PackedFixedBlock {
+            doc_ids: terms.iter().map(|term| term.doc_id).collect(),
+            field_mask_highs: terms
+                .iter()
+                .map(|term| (term.field_mask >> 64) as u64)
+                .collect(),
+            field_mask_lows: terms.iter().map(|term| term.field_mask as u64).collect(),
+            frequencies: terms.iter().map(|term| term.frequency).collect(),
+        }
+    }
+
+    pub fn from_proto_packed_fixed(
+        block: &block_proto::PackedFixedBlock,
+    ) -> Result<Self, &'static str> {
+        Self::from_columns(
+            &block.doc_ids,
+            &block.field_mask_highs,
+            &block.field_mask_lows,
+            &block.frequencies,
+        )
+    }
+
+    fn from_columns(
+        doc_ids: &[u64],
+        field_mask_highs: &[u64],
+        field_mask_lows: &[u64],
+        frequencies: &[u64],
+    ) -> Result<Self, &'static str> {
+        let num_terms = doc_ids.len();
+        if field_mask_highs.len() != num_terms
+            || field_mask_lows.len() != num_terms
+            || frequencies.len() != num_terms
+        {
+            return Err("Column lengths differ");
+        }
+
+        let full_terms = (0..num_terms)
+            .map(|i| FullTerm {
+                doc_id: doc_ids[i],
+                field_mask: ((field_mask_highs[i] as u128) << 64) | (field_mask_lows[i] as u128),
+                frequency: frequencies[i],
+            })
+            .collect();
+
+        Ok(Block { full_terms })
+    }
+}
+
+// FlatBuffers conversion helpers
+impl Block {
+    pub fn to_flatbuffers(&self, builder: &mut flatbuffers::FlatBufferBuilder) {
+        let terms: Vec<_> = self
+            .full_terms
+            .iter()
+            .map(|term| {
+                let field_mask =
+                    block_fbs::UInt128::new((term.field_mask >> 64) as u64, term.field_mask as u64);
+
+                block_fbs::FullTerm::create(
+                    builder,
+                    &block_fbs::FullTermArgs {
+                        doc_id: term.doc_id,
+                        field_mask: Some(&field_mask),
+                        frequency: term.frequency,
+                    },
+                )
+            })
+            .collect();
+
+        let full_terms = builder.create_vector(&terms);
+        let block = block_fbs::Block::create(
+            builder,
+            &block_fbs::BlockArgs {
+                full_terms: Some(full_terms),
+            },
+        );
+        builder.finish(block, None);
+    }
+
+    pub fn from_flatbuffers(block: block_fbs::Block) -> Self {
+        let full_terms = block
+            .full_terms()
+            .iter()
+            .flatten()
+            .map(|term| {
+                let field_mask = term.field_mask().map_or(0, |mask| {
+                    ((mask.high() as u128) << 64) | (mask.low() as u128)
+                });
+
+                FullTerm {
+                    doc_id: term.doc_id(),
+                    field_mask,
+                    frequency: term.frequency(),
+                }
+            })
+            .collect();
+
+        Block { full_terms }
+    }
 }
 
 // Include the generated Cap'n Proto code
@@ -81,6 +379,21 @@ pub mod block_capnp {
     include!(concat!(env!("OUT_DIR"), "/block_capnp.rs"));
 }
 
+pub mod block_flat_capnp {
+    include!(concat!(env!("OUT_DIR"), "/block_flat_capnp.rs"));
+}
+
+// Include the generated FlatBuffers code
+#[allow(clippy::all, warnings)]
+pub mod block_fbs {
+    include!(concat!(env!("OUT_DIR"), "/block_generated.rs"));
+}
+
+// Include the generated Protocol Buffers code
+pub mod block_proto {
+    include!(concat!(env!("OUT_DIR"), "/block_proto.rs"));
+}
+
 /// Generate test data with 1M entries across blocks of 100 entries each
 pub fn generate_test_data() -> Vec<Block> {
     const TOTAL_ENTRIES: usize = 1_000_000;
@@ -98,7 +411,7 @@ pub fn generate_test_data() -> Vec<Block> {
 
         for _ in 0..ENTRIES_PER_BLOCK {
             // Increment doc_id, occasionally with gaps
-            current_doc_id += if rng.next() % 10 == 0 {
+            current_doc_id += if rng.next().is_multiple_of(10) {
                 rng.next() % 5 + 1 // Gap of 1-5
             } else {
                 1 // Sequential
@@ -117,6 +430,105 @@ pub fn generate_test_data() -> Vec<Block> {
     blocks
 }
 
+/// Same blocks as [`generate_test_data`], but with every field mask drawn from
+/// a pool of `num_masks` sparse masks (one or two fields each), which is closer
+/// to the mask cardinality of a real index
+///
+/// Panics if `num_masks` is 0.
+pub fn generate_test_data_with_distinct_masks(num_masks: usize) -> Vec<Block> {
+    assert!(num_masks > 0, "num_masks must be at least 1");
+
+    let mut rng = Xorshift64::new(7);
+
+    let masks: Vec<u128> = (0..num_masks)
+        .map(|_| (1u128 << (rng.next() % 128)) | (1u128 << (rng.next() % 128)))
+        .collect();
+
+    let mut blocks = generate_test_data();
+    for term in blocks
+        .iter_mut()
+        .flat_map(|block| block.full_terms.iter_mut())
+    {
+        term.field_mask = masks[(rng.next() % num_masks as u64) as usize];
+    }
+
+    blocks
+}
+
+/// Same blocks as [`generate_test_data`], but every block only uses
+/// `masks_per_block` masks out of a pool of `num_masks` sparse masks, so a
+/// field that is rare across the pool is absent from most blocks
+///
+/// Panics if `num_masks` or `masks_per_block` is 0.
+pub fn generate_test_data_with_clustered_masks(
+    num_masks: usize,
+    masks_per_block: usize,
+) -> Vec<Block> {
+    assert!(num_masks > 0, "num_masks must be at least 1");
+    assert!(masks_per_block > 0, "masks_per_block must be at least 1");
+
+    let mut rng = Xorshift64::new(11);
+
+    let masks: Vec<u128> = (0..num_masks)
+        .map(|_| (1u128 << (rng.next() % 128)) | (1u128 << (rng.next() % 128)))
+        .collect();
+
+    let mut blocks = generate_test_data();
+    for block in &mut blocks {
+        let block_masks: Vec<u128> = (0..masks_per_block)
+            .map(|_| masks[(rng.next() % num_masks as u64) as usize])
+            .collect();
+
+        for term in &mut block.full_terms {
+            term.field_mask = block_masks[(rng.next() % masks_per_block as u64) as usize];
+        }
+    }
+
+    blocks
+}
+
+/// Posting list for one term over doc ids `0..num_docs`, keeping each doc with
+/// probability `density` and packing the kept docs into blocks of 100 terms
+pub fn generate_postings(seed: u64, num_docs: u64, density: f64) -> Vec<Block> {
+    const ENTRIES_PER_BLOCK: usize = 100;
+
+    let mut rng = Xorshift64::new(seed);
+    let threshold = (density * u64::MAX as f64) as u64;
+
+    let mut terms = Vec::new();
+    for doc_id in 0..num_docs {
+        if rng.next() <= threshold {
+            terms.push(FullTerm {
+                doc_id,
+                field_mask: rng.next_u128(),
+                frequency: rng.next() % 1000 + 1,
+            });
+        }
+    }
+
+    terms
+        .chunks(ENTRIES_PER_BLOCK)
+        .map(|chunk| Block {
+            full_terms: chunk.to_vec(),
+        })
+        .collect()
+}
+
+/// Number of leading indices in `0..len` for which `pred` holds, assuming it
+/// holds for a prefix of the range (`slice::partition_point` without a slice)
+pub(crate) fn partition_point(len: usize, mut pred: impl FnMut(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
 // Simple, deterministic PRNG for test data generation
 pub struct Xorshift64 {
     state: u64,
@@ -129,6 +541,7 @@ impl Xorshift64 {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> u64 {
         let mut x = self.state;
         x ^= x << 13;
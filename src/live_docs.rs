@@ -0,0 +1,164 @@
+//! Live-docs bitset for deleting terms from any block format
+//!
+//! A [`LiveDocs`] marks deleted terms by their position in a block, so it
+//! works the same for every format without touching the serialized bytes; a
+//! segment keeps one per block. [`LiveDocs::filter`] wraps any reader's term
+//! iterator (every reader yields terms in position order) and drops deleted
+//! ones, and [`LiveDocs::is_live`] serves readers that produce positions
+//! directly, like [`manual_roaring`](crate::manual_roaring).
+//!
+//! A block without deletions allocates nothing and the filter passes terms
+//! straight through. For the fixed-size manual layout, deletions can also be
+//! written into the block itself with [`crate::manual_zerocopy::TermMut::tombstone`].
+
+use std::iter::Enumerate;
+
+/// Deleted term positions of one block
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveDocs {
+    num_terms: usize,
+    num_deleted: usize,
+    /// One bit per term, set when deleted; empty until the first deletion
+    deleted: Vec<u64>,
+}
+
+impl LiveDocs {
+    /// All `num_terms` terms live
+    pub fn new(num_terms: usize) -> Self {
+        LiveDocs {
+            num_terms,
+            ..Self::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.num_terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    pub fn num_deleted(&self) -> usize {
+        self.num_deleted
+    }
+
+    /// Delete the term at `position`, returning false if it was already
+    /// deleted or is out of range
+    pub fn delete(&mut self, position: usize) -> bool {
+        if !self.is_live(position) {
+            return false;
+        }
+
+        if self.deleted.is_empty() {
+            self.deleted = vec![0; self.num_terms.div_ceil(64)];
+        }
+
+        self.deleted[position / 64] |= 1 << (position % 64);
+        self.num_deleted += 1;
+
+        true
+    }
+
+    #[inline(always)]
+    pub fn is_live(&self, position: usize) -> bool {
+        position < self.num_terms
+            && self
+                .deleted
+                .get(position / 64)
+                .is_none_or(|word| word & (1 << (position % 64)) == 0)
+    }
+
+    /// Drop the deleted terms from a block's terms, given in position order
+    pub fn filter<I: IntoIterator>(&self, terms: I) -> LiveIter<'_, I::IntoIter> {
+        LiveIter {
+            terms: terms.into_iter().enumerate(),
+            deleted: (self.num_deleted > 0).then_some(&self.deleted[..]),
+        }
+    }
+}
+
+/// Iterator over the live terms of a block
+pub struct LiveIter<'a, I> {
+    terms: Enumerate<I>,
+    deleted: Option<&'a [u64]>,
+}
+
+impl<'a, I: Iterator> Iterator for LiveIter<'a, I> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(deleted) = self.deleted else {
+            return self.terms.next().map(|(_, term)| term);
+        };
+
+        self.terms.find_map(|(position, term)| {
+            let word = deleted.get(position / 64).copied().unwrap_or(0);
+            (word & (1 << (position % 64)) == 0).then_some(term)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_test_data, manual_roaring, manual_zerocopy};
+
+    #[test]
+    fn test_delete() {
+        let mut live_docs = LiveDocs::new(130);
+        assert_eq!(live_docs.filter(0..130).count(), 130);
+
+        assert!(live_docs.delete(0));
+        assert!(live_docs.delete(64));
+        assert!(live_docs.delete(129));
+        assert!(!live_docs.delete(64));
+        assert!(!live_docs.delete(130));
+
+        assert_eq!(live_docs.num_deleted(), 3);
+        assert!(!live_docs.is_live(64));
+        assert!(live_docs.is_live(65));
+        assert!(!live_docs.is_live(130));
+
+        let live: Vec<usize> = live_docs.filter(0..130).collect();
+        assert_eq!(live.len(), 127);
+        assert_eq!(live[0], 1);
+        assert!(!live.contains(&64));
+    }
+
+    #[test]
+    fn test_readers_skip_deleted() {
+        let block = &generate_test_data()[0];
+
+        let mut live_docs = LiveDocs::new(block.full_terms.len());
+        for position in (0..block.full_terms.len()).step_by(3) {
+            live_docs.delete(position);
+        }
+
+        let expected: Vec<u64> = live_docs
+            .filter(&block.full_terms)
+            .map(|term| term.doc_id)
+            .collect();
+        assert_eq!(expected.len(), 66);
+
+        let bytes = manual_zerocopy::serialize(block);
+        let reader = manual_zerocopy::BlockReader::new(&bytes).unwrap();
+        let doc_ids: Vec<u64> = live_docs
+            .filter(reader.iter())
+            .map(|term| term.doc_id())
+            .collect();
+        assert_eq!(doc_ids, expected);
+
+        let bytes = manual_roaring::serialize(block);
+        let reader = manual_roaring::BlockReader::new(&bytes).unwrap();
+        let doc_ids: Vec<u64> = reader
+            .matching(!0)
+            .unwrap()
+            .iter()
+            .filter(|&position| live_docs.is_live(position as usize))
+            .map(|position| reader.doc_id(position as usize))
+            .collect();
+        assert_eq!(doc_ids, expected);
+    }
+}
@@ -0,0 +1,442 @@
+//! Manual frame-of-reference bit-packed serialization
+//!
+//! Modelled on the FOR blocks used by postings engines such as Lucene. Both
+//! the doc id and the frequency column subtract a per-block minimum and pack
+//! what is left with the smallest bit width that fits the block. Field masks
+//! stay as raw `u128`s so a filter can test them without unpacking anything.
+//!
+//! Unlike Lucene, doc ids are stored as offsets from the block's minimum doc
+//! id, not as deltas between neighbours. This is deliberate: deltas have to
+//! be summed from the start of the block to recover any doc id, so a filtered
+//! scan would unpack every doc id it passes. Offsets can be read at any
+//! position, so a filtered scan only unpacks the terms it keeps. The price is
+//! a wider doc id column, since an offset spans the whole block's doc id range
+//! while a delta only spans the gap to the previous term.
+//!
+//! Readers add the minimums with wrapping arithmetic, so a corrupt header
+//! yields wrong values instead of an overflow panic.
+//!
+//! Layout (all little-endian):
+//! - header: `u32` term count, `u64` minimum doc id, `u8` doc offset width,
+//!   `u64` minimum frequency, `u8` frequency width
+//! - `num_terms` field masks, 16 bytes each
+//! - `num_terms` packed doc offsets
+//! - `num_terms` packed frequencies
+
+use crate::{Block, FullTerm};
+
+const HEADER_SIZE: usize = 22; // 4 + 8 + 1 + 8 + 1
+const FIELD_MASK_SIZE: usize = 16;
+
+/// Smallest bit width that can hold `value`
+#[inline]
+fn bit_width(value: u64) -> u8 {
+    (u64::BITS - value.leading_zeros()) as u8
+}
+
+/// Number of bytes needed to pack `count` values of `width` bits
+#[inline]
+pub(crate) fn packed_len(count: usize, width: u8) -> usize {
+    (count * width as usize).div_ceil(8)
+}
+
+/// Append `values` (already offset by the block minimum) packed LSB-first at
+/// `width` bits each
+pub(crate) fn pack(values: impl Iterator<Item = u64>, width: u8, bytes: &mut Vec<u8>) {
+    if width == 0 {
+        return;
+    }
+
+    let mut buffer = 0u128;
+    let mut buffered_bits = 0u32;
+
+    for value in values {
+        buffer |= (value as u128) << buffered_bits;
+        buffered_bits += width as u32;
+
+        while buffered_bits >= 8 {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            buffered_bits -= 8;
+        }
+    }
+
+    if buffered_bits > 0 {
+        bytes.push(buffer as u8);
+    }
+}
+
+/// Unpack the value at `index` from a column packed at `width` bits
+#[inline(always)]
+pub(crate) fn unpack(packed: &[u8], width: u8, index: usize) -> u64 {
+    if width == 0 {
+        return 0;
+    }
+
+    let bit = index * width as usize;
+    let byte = bit / 8;
+    let shift = bit % 8;
+
+    // A value spans at most 9 bytes, so a 16-byte window always covers it.
+    // Only the last few values of a column need the padded slow path.
+    let window = match packed.get(byte..byte + 16) {
+        Some(window) => u128::from_le_bytes(window.try_into().unwrap()),
+        None => {
+            let mut window = [0u8; 16];
+            let tail = &packed[byte..];
+            window[..tail.len()].copy_from_slice(tail);
+            u128::from_le_bytes(window)
+        }
+    };
+
+    let mask = u64::MAX >> (64 - width as u32);
+    (window >> shift) as u64 & mask
+}
+
+#[inline(always)]
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// Serialize a block to bytes using the bit-packed layout
+pub fn serialize(block: &Block) -> Vec<u8> {
+    let terms = &block.full_terms;
+    let num_terms = terms.len();
+
+    let min_doc_id = terms.iter().map(|term| term.doc_id).min().unwrap_or(0);
+    let max_doc_id = terms.iter().map(|term| term.doc_id).max().unwrap_or(0);
+    let doc_offset_width = bit_width(max_doc_id - min_doc_id);
+
+    let min_frequency = terms.iter().map(|term| term.frequency).min().unwrap_or(0);
+    let max_frequency = terms.iter().map(|term| term.frequency).max().unwrap_or(0);
+    let frequency_width = bit_width(max_frequency - min_frequency);
+
+    let total_size = HEADER_SIZE
+        + num_terms * FIELD_MASK_SIZE
+        + packed_len(num_terms, doc_offset_width)
+        + packed_len(num_terms, frequency_width);
+    let mut bytes = Vec::with_capacity(total_size);
+
+    bytes.extend_from_slice(&(num_terms as u32).to_le_bytes());
+    bytes.extend_from_slice(&min_doc_id.to_le_bytes());
+    bytes.push(doc_offset_width);
+    bytes.extend_from_slice(&min_frequency.to_le_bytes());
+    bytes.push(frequency_width);
+
+    for term in terms {
+        bytes.extend_from_slice(&term.field_mask.to_le_bytes());
+    }
+
+    pack(
+        terms.iter().map(|term| term.doc_id - min_doc_id),
+        doc_offset_width,
+        &mut bytes,
+    );
+    pack(
+        terms.iter().map(|term| term.frequency - min_frequency),
+        frequency_width,
+        &mut bytes,
+    );
+
+    bytes
+}
+
+/// Deserialize a block from bytes (full deserialization)
+pub fn deserialize(bytes: &[u8]) -> Result<Block, &'static str> {
+    let reader = BlockReader::new(bytes)?;
+
+    let full_terms = reader.iter().map(|term| term.deserialize()).collect();
+
+    Ok(Block { full_terms })
+}
+
+/// Reader over a bit-packed block
+///
+/// Every column can be read at any position without unpacking the others.
+pub struct BlockReader<'a> {
+    num_terms: usize,
+    min_doc_id: u64,
+    doc_offset_width: u8,
+    min_frequency: u64,
+    frequency_width: u8,
+    field_masks: &'a [u8],
+    doc_offsets: &'a [u8],
+    frequencies: &'a [u8],
+}
+
+impl<'a> BlockReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        if bytes.len() < HEADER_SIZE {
+            return Err("Buffer too small for header");
+        }
+
+        let num_terms = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+        let min_doc_id = read_u64(bytes, 4);
+        let doc_offset_width = bytes[12];
+        let min_frequency = read_u64(bytes, 13);
+        let frequency_width = bytes[21];
+
+        if doc_offset_width > 64 || frequency_width > 64 {
+            return Err("Invalid bit width");
+        }
+
+        let field_masks_end = HEADER_SIZE + num_terms * FIELD_MASK_SIZE;
+        let doc_offsets_end = field_masks_end + packed_len(num_terms, doc_offset_width);
+        let frequencies_end = doc_offsets_end + packed_len(num_terms, frequency_width);
+
+        if bytes.len() < frequencies_end {
+            return Err("Buffer too small for data");
+        }
+
+        Ok(BlockReader {
+            num_terms,
+            min_doc_id,
+            doc_offset_width,
+            min_frequency,
+            frequency_width,
+            field_masks: &bytes[HEADER_SIZE..field_masks_end],
+            doc_offsets: &bytes[field_masks_end..doc_offsets_end],
+            frequencies: &bytes[doc_offsets_end..frequencies_end],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.num_terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    /// Read the field mask at `index` without unpacking anything
+    #[inline(always)]
+    pub fn field_mask(&self, index: usize) -> u128 {
+        let offset = index * FIELD_MASK_SIZE;
+        u128::from_le_bytes(
+            self.field_masks[offset..offset + FIELD_MASK_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Unpack the frequency at `index`
+    #[inline(always)]
+    pub fn frequency(&self, index: usize) -> u64 {
+        self.min_frequency
+            .wrapping_add(unpack(self.frequencies, self.frequency_width, index))
+    }
+
+    /// Unpack the doc id at `index`
+    #[inline(always)]
+    pub fn doc_id(&self, index: usize) -> u64 {
+        self.min_doc_id
+            .wrapping_add(unpack(self.doc_offsets, self.doc_offset_width, index))
+    }
+
+    pub fn iter(&self) -> TermIterator<'_, 'a> {
+        TermIterator {
+            reader: self,
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over terms in a bit-packed block
+///
+/// Doc ids and frequencies are only unpacked when [`TermReader::doc_id`] and
+/// [`TermReader::frequency`] are called.
+pub struct TermIterator<'r, 'a> {
+    reader: &'r BlockReader<'a>,
+    index: usize,
+}
+
+impl<'r, 'a> Iterator for TermIterator<'r, 'a> {
+    type Item = TermReader<'r, 'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.reader.num_terms {
+            return None;
+        }
+
+        let term = TermReader {
+            reader: self.reader,
+            index: self.index,
+        };
+
+        self.index += 1;
+
+        Some(term)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.reader.num_terms - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'r, 'a> ExactSizeIterator for TermIterator<'r, 'a> {}
+
+/// Reader for a single term in a bit-packed block
+pub struct TermReader<'r, 'a> {
+    reader: &'r BlockReader<'a>,
+    index: usize,
+}
+
+impl<'r, 'a> TermReader<'r, 'a> {
+    /// Unpack doc_id
+    #[inline(always)]
+    pub fn doc_id(&self) -> u64 {
+        self.reader.doc_id(self.index)
+    }
+
+    /// Access field_mask
+    #[inline(always)]
+    pub fn field_mask(&self) -> u128 {
+        self.reader.field_mask(self.index)
+    }
+
+    /// Unpack frequency
+    #[inline(always)]
+    pub fn frequency(&self) -> u64 {
+        self.reader.frequency(self.index)
+    }
+
+    /// Deserialize into a FullTerm
+    #[inline]
+    pub fn deserialize(&self) -> FullTerm {
+        FullTerm {
+            doc_id: self.doc_id(),
+            field_mask: self.field_mask(),
+            frequency: self.frequency(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roundtrip(block: &Block) {
+        let bytes = serialize(block);
+        let deserialized = deserialize(&bytes).unwrap();
+
+        // Every column reads by position, without walking the block
+        let reader = BlockReader::new(&bytes).unwrap();
+        for (index, expected) in block.full_terms.iter().enumerate().rev() {
+            assert_eq!(reader.doc_id(index), expected.doc_id);
+            assert_eq!(reader.frequency(index), expected.frequency);
+        }
+
+        assert_eq!(block.full_terms.len(), deserialized.full_terms.len());
+        for (expected, actual) in block.full_terms.iter().zip(&deserialized.full_terms) {
+            assert_eq!(expected.doc_id, actual.doc_id);
+            assert_eq!(expected.field_mask, actual.field_mask);
+            assert_eq!(expected.frequency, actual.frequency);
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let block = Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 1,
+                    field_mask: 0xDEADBEEF,
+                    frequency: 42,
+                },
+                FullTerm {
+                    doc_id: 2,
+                    field_mask: 0xCAFEBABE,
+                    frequency: 123,
+                },
+                FullTerm {
+                    doc_id: 7,
+                    field_mask: (0xFF00FF00 << 64) | 0x1234,
+                    frequency: 1,
+                },
+            ],
+        };
+
+        assert_roundtrip(&block);
+    }
+
+    #[test]
+    fn test_generated_data_roundtrip() {
+        for block in crate::generate_test_data().iter().take(100) {
+            assert!(serialize(block).len() < crate::manual_zerocopy::serialize(block).len());
+            assert_roundtrip(block);
+        }
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let blocks = [
+            Block { full_terms: vec![] },
+            // Constant columns pack at a width of zero
+            Block {
+                full_terms: vec![
+                    FullTerm {
+                        doc_id: 5,
+                        field_mask: 1,
+                        frequency: 3,
+                    };
+                    3
+                ],
+            },
+            // Unsorted doc ids and extreme values need the full 64 bits
+            Block {
+                full_terms: vec![
+                    FullTerm {
+                        doc_id: u64::MAX,
+                        field_mask: u128::MAX,
+                        frequency: 0,
+                    },
+                    FullTerm {
+                        doc_id: 0,
+                        field_mask: 0,
+                        frequency: u64::MAX,
+                    },
+                    FullTerm {
+                        doc_id: u64::MAX / 2,
+                        field_mask: 7,
+                        frequency: 1,
+                    },
+                ],
+            },
+        ];
+
+        for block in &blocks {
+            assert_roundtrip(block);
+        }
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        let block = Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 100,
+                    field_mask: 0xFF00FF00,
+                    frequency: 7,
+                },
+                FullTerm {
+                    doc_id: 103,
+                    field_mask: 0xFF,
+                    frequency: 9,
+                },
+            ],
+        };
+
+        let bytes = serialize(&block);
+
+        assert!(BlockReader::new(&bytes[..2]).is_err());
+        assert!(BlockReader::new(&bytes[..bytes.len() - 1]).is_err());
+
+        // Minimums whose offsets overflow read as garbage, not a panic
+        let mut corrupt = bytes.clone();
+        corrupt[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        corrupt[13..21].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(deserialize(&corrupt).is_ok());
+    }
+}
@@ -0,0 +1,276 @@
+//! Manual block format with an Elias–Fano doc_id column
+//!
+//! Stores the block column by column: the doc ids as an [`elias_fano`] column,
+//! followed by the raw little-endian field masks and frequencies. Doc ids can
+//! be read at any position and searched with [`BlockReader::next_geq`] without
+//! decoding the column.
+//!
+//! Elias–Fano needs sorted input, so serialization fails for blocks whose doc
+//! ids are not in non-decreasing order.
+
+use crate::elias_fano::{self, EliasFano};
+use crate::{Block, FullTerm};
+
+const FIELD_MASK_SIZE: usize = 16;
+const FREQUENCY_SIZE: usize = 8;
+
+/// Serialize a block to bytes using the Elias–Fano column layout
+pub fn serialize(block: &Block) -> Result<Vec<u8>, &'static str> {
+    let doc_ids: Vec<u64> = block.full_terms.iter().map(|term| term.doc_id).collect();
+
+    let mut bytes = Vec::new();
+    elias_fano::encode(&doc_ids, &mut bytes)?;
+
+    bytes.reserve(block.full_terms.len() * (FIELD_MASK_SIZE + FREQUENCY_SIZE));
+    for term in &block.full_terms {
+        bytes.extend_from_slice(&term.field_mask.to_le_bytes());
+    }
+    for term in &block.full_terms {
+        bytes.extend_from_slice(&term.frequency.to_le_bytes());
+    }
+
+    Ok(bytes)
+}
+
+/// Deserialize a block from bytes (full deserialization)
+pub fn deserialize(bytes: &[u8]) -> Result<Block, &'static str> {
+    let reader = BlockReader::new(bytes)?;
+
+    let full_terms = reader.iter().map(|term| term.deserialize()).collect();
+
+    Ok(Block { full_terms })
+}
+
+/// Zero-copy reader for accessing block data without full deserialization
+pub struct BlockReader<'a> {
+    doc_ids: EliasFano<'a>,
+    field_masks: &'a [u8],
+    frequencies: &'a [u8],
+}
+
+impl<'a> BlockReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        let (doc_ids, rest) = EliasFano::new(bytes)?;
+
+        let num_terms = doc_ids.len();
+        let field_masks_end = num_terms * FIELD_MASK_SIZE;
+        let frequencies_end = field_masks_end + num_terms * FREQUENCY_SIZE;
+
+        if rest.len() < frequencies_end {
+            return Err("Buffer too small for data");
+        }
+
+        Ok(BlockReader {
+            doc_ids,
+            field_masks: &rest[..field_masks_end],
+            frequencies: &rest[field_masks_end..frequencies_end],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.doc_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.doc_ids.is_empty()
+    }
+
+    /// Read the doc_id at `index`
+    #[inline]
+    pub fn doc_id(&self, index: usize) -> Option<u64> {
+        self.doc_ids.get(index)
+    }
+
+    /// Read the field mask at `index`
+    #[inline(always)]
+    pub fn field_mask(&self, index: usize) -> u128 {
+        let offset = index * FIELD_MASK_SIZE;
+        u128::from_le_bytes(
+            self.field_masks[offset..offset + FIELD_MASK_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Read the frequency at `index`
+    #[inline(always)]
+    pub fn frequency(&self, index: usize) -> u64 {
+        let offset = index * FREQUENCY_SIZE;
+        u64::from_le_bytes(
+            self.frequencies[offset..offset + FREQUENCY_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// First term whose doc_id is greater than or equal to `target`
+    pub fn next_geq(&self, target: u64) -> Option<TermReader<'_, 'a>> {
+        let (index, doc_id) = self.doc_ids.next_geq(target)?;
+
+        Some(TermReader {
+            reader: self,
+            index,
+            doc_id,
+        })
+    }
+
+    pub fn iter(&self) -> TermIterator<'_, 'a> {
+        TermIterator {
+            reader: self,
+            doc_ids: self.doc_ids.iter(),
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over terms in an Elias–Fano block, decoding doc ids sequentially
+pub struct TermIterator<'r, 'a> {
+    reader: &'r BlockReader<'a>,
+    doc_ids: elias_fano::Iter<'a>,
+    index: usize,
+}
+
+impl<'r, 'a> Iterator for TermIterator<'r, 'a> {
+    type Item = TermReader<'r, 'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let doc_id = self.doc_ids.next()?;
+
+        let term = TermReader {
+            reader: self.reader,
+            index: self.index,
+            doc_id,
+        };
+
+        self.index += 1;
+
+        Some(term)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.doc_ids.size_hint()
+    }
+}
+
+impl<'r, 'a> ExactSizeIterator for TermIterator<'r, 'a> {}
+
+/// Reader for a single term in an Elias–Fano block
+pub struct TermReader<'r, 'a> {
+    reader: &'r BlockReader<'a>,
+    index: usize,
+    doc_id: u64,
+}
+
+impl<'r, 'a> TermReader<'r, 'a> {
+    /// Position of this term in the block
+    #[inline(always)]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Access doc_id (already decoded)
+    #[inline(always)]
+    pub fn doc_id(&self) -> u64 {
+        self.doc_id
+    }
+
+    /// Access field_mask
+    #[inline(always)]
+    pub fn field_mask(&self) -> u128 {
+        self.reader.field_mask(self.index)
+    }
+
+    /// Access frequency
+    #[inline(always)]
+    pub fn frequency(&self) -> u64 {
+        self.reader.frequency(self.index)
+    }
+
+    /// Deserialize into a FullTerm
+    #[inline]
+    pub fn deserialize(&self) -> FullTerm {
+        FullTerm {
+            doc_id: self.doc_id(),
+            field_mask: self.field_mask(),
+            frequency: self.frequency(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_block() -> Block {
+        Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 100,
+                    field_mask: 0xDEADBEEF,
+                    frequency: 42,
+                },
+                FullTerm {
+                    doc_id: 101,
+                    field_mask: (0xFF00FF00 << 64) | 0x1234,
+                    frequency: 123,
+                },
+                FullTerm {
+                    doc_id: 150,
+                    field_mask: 0xCAFEBABE,
+                    frequency: 7,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let block = test_block();
+
+        let bytes = serialize(&block).unwrap();
+        let deserialized = deserialize(&bytes).unwrap();
+
+        assert_eq!(block.full_terms.len(), deserialized.full_terms.len());
+        for (expected, actual) in block.full_terms.iter().zip(&deserialized.full_terms) {
+            assert_eq!(expected.doc_id, actual.doc_id);
+            assert_eq!(expected.field_mask, actual.field_mask);
+            assert_eq!(expected.frequency, actual.frequency);
+        }
+    }
+
+    #[test]
+    fn test_next_geq() {
+        let bytes = serialize(&test_block()).unwrap();
+        let reader = BlockReader::new(&bytes).unwrap();
+
+        let term = reader.next_geq(101).unwrap();
+        assert_eq!(term.index(), 1);
+        assert_eq!(term.doc_id(), 101);
+        assert_eq!(term.frequency(), 123);
+
+        let term = reader.next_geq(102).unwrap();
+        assert_eq!(term.doc_id(), 150);
+        assert_eq!(term.field_mask(), 0xCAFEBABE);
+
+        assert_eq!(reader.next_geq(0).unwrap().doc_id(), 100);
+        assert!(reader.next_geq(151).is_none());
+        assert_eq!(reader.doc_id(2), Some(150));
+    }
+
+    #[test]
+    fn test_unsorted_doc_ids() {
+        let mut block = test_block();
+        block.full_terms.swap(0, 2);
+
+        assert!(serialize(&block).is_err());
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        let bytes = serialize(&test_block()).unwrap();
+
+        assert!(BlockReader::new(&bytes[..2]).is_err());
+        assert!(BlockReader::new(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
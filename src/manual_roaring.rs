@@ -0,0 +1,377 @@
+//! Manual inverted block format with one roaring bitmap per field bit
+//!
+//! Instead of storing a `field_mask` per term, the block stores, for every
+//! field bit that is set on at least one term, a roaring bitmap of the term
+//! positions that have it. Positions index into the doc_id and frequency
+//! columns, so a bitmap of positions identifies the matching doc ids. A
+//! filtered read is the union of the bitmaps for the query's bits, and never
+//! looks at terms that do not match.
+//!
+//! Layout (all little-endian):
+//! - header: `u32` term count, `u128` mask of the field bits that have a bitmap
+//! - one `u32` end offset per bitmap, relative to the start of the bitmaps
+//! - bitmaps in roaring's portable serialization, in field bit order
+//! - `num_terms` doc ids (`u64`)
+//! - `num_terms` frequencies (`u64`)
+
+use roaring::RoaringBitmap;
+
+use crate::predicate::{MaskCondition, Predicate};
+use crate::{Block, FullTerm};
+
+const HEADER_SIZE: usize = 20; // 4 + 16
+const OFFSET_SIZE: usize = 4;
+const DOC_ID_SIZE: usize = 8;
+const FREQUENCY_SIZE: usize = 8;
+
+/// Serialize a block to bytes using the per-field bitmap layout
+pub fn serialize(block: &Block) -> Vec<u8> {
+    let num_terms = block.full_terms.len();
+
+    let mut bitmaps: Vec<RoaringBitmap> = vec![RoaringBitmap::new(); 128];
+    let mut present = 0u128;
+    for (position, term) in block.full_terms.iter().enumerate() {
+        present |= term.field_mask;
+
+        let mut mask = term.field_mask;
+        while mask != 0 {
+            let bit = mask.trailing_zeros() as usize;
+            bitmaps[bit].insert(position as u32);
+            mask &= mask - 1;
+        }
+    }
+
+    bitmaps.retain(|bitmap| !bitmap.is_empty());
+
+    let bitmaps_size: usize = bitmaps.iter().map(|bitmap| bitmap.serialized_size()).sum();
+    let total_size = HEADER_SIZE
+        + bitmaps.len() * OFFSET_SIZE
+        + bitmaps_size
+        + num_terms * (DOC_ID_SIZE + FREQUENCY_SIZE);
+
+    let mut bytes = Vec::with_capacity(total_size);
+
+    bytes.extend_from_slice(&(num_terms as u32).to_le_bytes());
+    bytes.extend_from_slice(&present.to_le_bytes());
+
+    let mut end = 0;
+    for bitmap in &bitmaps {
+        end += bitmap.serialized_size();
+        bytes.extend_from_slice(&(end as u32).to_le_bytes());
+    }
+    for bitmap in &bitmaps {
+        bitmap
+            .serialize_into(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+    }
+
+    for term in &block.full_terms {
+        bytes.extend_from_slice(&term.doc_id.to_le_bytes());
+    }
+    for term in &block.full_terms {
+        bytes.extend_from_slice(&term.frequency.to_le_bytes());
+    }
+
+    bytes
+}
+
+/// Deserialize a block from bytes (full deserialization, rebuilding every
+/// term's field mask from the bitmaps)
+pub fn deserialize(bytes: &[u8]) -> Result<Block, &'static str> {
+    let reader = BlockReader::new(bytes)?;
+
+    let mut full_terms: Vec<FullTerm> = (0..reader.len())
+        .map(|i| FullTerm {
+            doc_id: reader.doc_id(i),
+            field_mask: 0,
+            frequency: reader.frequency(i),
+        })
+        .collect();
+
+    let mut present = reader.present;
+    while present != 0 {
+        let bit = present.trailing_zeros();
+        let bitmap = reader.bitmap(bit)?.ok_or("Missing bitmap")?;
+
+        for position in &bitmap {
+            full_terms
+                .get_mut(position as usize)
+                .ok_or("Bitmap position out of range")?
+                .field_mask |= 1 << bit;
+        }
+
+        present &= present - 1;
+    }
+
+    Ok(Block { full_terms })
+}
+
+/// Reader for accessing block data without full deserialization
+///
+/// The doc_id and frequency columns are read in place; bitmaps are decoded on
+/// demand.
+pub struct BlockReader<'a> {
+    num_terms: usize,
+    present: u128,
+    offsets: &'a [u8],
+    bitmaps: &'a [u8],
+    doc_ids: &'a [u8],
+    frequencies: &'a [u8],
+}
+
+impl<'a> BlockReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        if bytes.len() < HEADER_SIZE {
+            return Err("Buffer too small for header");
+        }
+
+        let num_terms = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+        let present = u128::from_le_bytes(bytes[4..20].try_into().unwrap());
+
+        let num_bitmaps = present.count_ones() as usize;
+        let offsets_end = HEADER_SIZE + num_bitmaps * OFFSET_SIZE;
+        if bytes.len() < offsets_end {
+            return Err("Buffer too small for data");
+        }
+
+        let offsets = &bytes[HEADER_SIZE..offsets_end];
+        let bitmaps_size = match num_bitmaps {
+            0 => 0,
+            n => read_offset(offsets, n - 1),
+        };
+
+        let bitmaps_end = offsets_end + bitmaps_size;
+        let doc_ids_end = bitmaps_end + num_terms * DOC_ID_SIZE;
+        let frequencies_end = doc_ids_end + num_terms * FREQUENCY_SIZE;
+
+        if bytes.len() < frequencies_end {
+            return Err("Buffer too small for data");
+        }
+
+        Ok(BlockReader {
+            num_terms,
+            present,
+            offsets,
+            bitmaps: &bytes[offsets_end..bitmaps_end],
+            doc_ids: &bytes[bitmaps_end..doc_ids_end],
+            frequencies: &bytes[doc_ids_end..frequencies_end],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.num_terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    /// Field bits set on at least one term in the block
+    pub fn present_fields(&self) -> u128 {
+        self.present
+    }
+
+    /// Decode the bitmap of term positions that have field `bit` set, or
+    /// `None` if no term in the block has it
+    pub fn bitmap(&self, bit: u32) -> Result<Option<RoaringBitmap>, &'static str> {
+        if bit >= 128 || self.present & (1 << bit) == 0 {
+            return Ok(None);
+        }
+
+        // The bitmap's slot is the number of present bits below it
+        let slot = (self.present & ((1 << bit) - 1)).count_ones() as usize;
+        let start = match slot {
+            0 => 0,
+            slot => read_offset(self.offsets, slot - 1),
+        };
+        let end = read_offset(self.offsets, slot);
+
+        let bitmap_bytes = self
+            .bitmaps
+            .get(start..end)
+            .ok_or("Invalid bitmap offset")?;
+
+        RoaringBitmap::deserialize_from(bitmap_bytes)
+            .map(Some)
+            .map_err(|_| "Invalid bitmap")
+    }
+
+    /// Union of the bitmaps for every bit in `query_mask`: the positions of
+    /// all terms whose field mask intersects the query
+    pub fn matching(&self, query_mask: u128) -> Result<RoaringBitmap, &'static str> {
+        let mut matching = RoaringBitmap::new();
+
+        let mut bits = query_mask & self.present;
+        while bits != 0 {
+            if let Some(bitmap) = self.bitmap(bits.trailing_zeros())? {
+                matching |= bitmap;
+            }
+
+            bits &= bits - 1;
+        }
+
+        // Keep malformed bitmaps from pointing past the columns
+        matching.remove_range(self.num_terms as u32..);
+
+        Ok(matching)
+    }
+
+    /// Positions of all terms that match `predicate`
+    ///
+    /// `All` intersects the bitmaps of the query bits and `NoneOf` subtracts
+    /// their union from the full block; frequency bounds are then checked per
+    /// remaining position.
+    pub fn matching_predicate(&self, predicate: &Predicate) -> Result<RoaringBitmap, &'static str> {
+        let mut all_terms = RoaringBitmap::new();
+        all_terms.insert_range(0..self.num_terms as u32);
+
+        let mut matching = match predicate.mask {
+            MaskCondition::Always => all_terms,
+            MaskCondition::Any(query_mask) => self.matching(query_mask)?,
+            MaskCondition::All(query_mask) if query_mask & !self.present != 0 => {
+                // Some query bit is set on no term at all
+                RoaringBitmap::new()
+            }
+            MaskCondition::All(query_mask) => {
+                let mut matching = all_terms;
+
+                let mut bits = query_mask;
+                while bits != 0 && !matching.is_empty() {
+                    matching &= self
+                        .bitmap(bits.trailing_zeros())?
+                        .ok_or("Missing bitmap")?;
+
+                    bits &= bits - 1;
+                }
+
+                matching
+            }
+            MaskCondition::NoneOf(query_mask) => all_terms - self.matching(query_mask)?,
+        };
+
+        if predicate.has_frequency_bounds() {
+            matching = matching
+                .iter()
+                .filter(|&position| predicate.matches_frequency(self.frequency(position as usize)))
+                .collect();
+        }
+
+        Ok(matching)
+    }
+
+    /// Read the doc_id of the term at `position`
+    #[inline(always)]
+    pub fn doc_id(&self, position: usize) -> u64 {
+        let offset = position * DOC_ID_SIZE;
+        u64::from_le_bytes(
+            self.doc_ids[offset..offset + DOC_ID_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Read the frequency of the term at `position`
+    #[inline(always)]
+    pub fn frequency(&self, position: usize) -> u64 {
+        let offset = position * FREQUENCY_SIZE;
+        u64::from_le_bytes(
+            self.frequencies[offset..offset + FREQUENCY_SIZE]
+                .try_into()
+                .unwrap(),
+        )
+    }
+}
+
+#[inline(always)]
+fn read_offset(offsets: &[u8], slot: usize) -> usize {
+    let offset = slot * OFFSET_SIZE;
+    u32::from_le_bytes(offsets[offset..offset + OFFSET_SIZE].try_into().unwrap()) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_block() -> Block {
+        Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 10,
+                    field_mask: 0b0101,
+                    frequency: 42,
+                },
+                FullTerm {
+                    doc_id: 11,
+                    field_mask: 1 << 127,
+                    frequency: 123,
+                },
+                FullTerm {
+                    doc_id: 15,
+                    field_mask: 0,
+                    frequency: 5,
+                },
+                FullTerm {
+                    doc_id: 20,
+                    field_mask: 0b0110,
+                    frequency: 7,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let block = test_block();
+
+        let deserialized = deserialize(&serialize(&block)).unwrap();
+
+        assert_eq!(block.full_terms.len(), deserialized.full_terms.len());
+        for (expected, actual) in block.full_terms.iter().zip(&deserialized.full_terms) {
+            assert_eq!(expected.doc_id, actual.doc_id);
+            assert_eq!(expected.field_mask, actual.field_mask);
+            assert_eq!(expected.frequency, actual.frequency);
+        }
+    }
+
+    #[test]
+    fn test_matching() {
+        let bytes = serialize(&test_block());
+        let reader = BlockReader::new(&bytes).unwrap();
+
+        assert_eq!(reader.present_fields(), (1 << 127) | 0b0111);
+
+        let matching = reader.matching(0b0100).unwrap();
+        let doc_ids: Vec<u64> = matching.iter().map(|p| reader.doc_id(p as usize)).collect();
+        assert_eq!(doc_ids, [10, 20]);
+
+        let matching = reader.matching((1 << 127) | 0b0010).unwrap();
+        assert_eq!(matching.iter().collect::<Vec<_>>(), [1, 3]);
+
+        assert!(reader.matching(0b1000).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_generated_data_matches_scan() {
+        let query_mask = 0xFF;
+
+        for block in crate::generate_test_data().iter().take(10) {
+            let bytes = serialize(block);
+            let reader = BlockReader::new(&bytes).unwrap();
+
+            let expected: Vec<u32> = (0..block.full_terms.len() as u32)
+                .filter(|&i| block.full_terms[i as usize].field_mask & query_mask != 0)
+                .collect();
+            let matching = reader.matching(query_mask).unwrap();
+
+            assert_eq!(matching.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        let bytes = serialize(&test_block());
+
+        assert!(BlockReader::new(&bytes[..4]).is_err());
+        assert!(BlockReader::new(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -9,11 +9,26 @@
 //! - doc_id: u64 (8 bytes)
 //! - field_mask: u128 (16 bytes)
 //! - frequency: u64 (8 bytes)
+//!
 //! Total: 32 bytes per term
+//!
+//! A term with a frequency of [`TOMBSTONE_FREQUENCY`] is deleted. The value is
+//! out of band: [`BlockWriter::push`] and [`TermMut::set_frequency`] refuse to
+//! store it as a real frequency, so any frequency a term can have, including
+//! 0, stays live. Tombstones also have an empty field mask, so mask filters
+//! skip them without checking. [`serialize`], [`deserialize`] and the readers
+//! don't treat the value specially: a tombstone reads back as a term with that
+//! frequency and round-trips unchanged, so callers that sum or otherwise use
+//! frequencies must check [`TermReader::is_tombstone`] first.
+
+use std::ops::Range;
 
-use crate::{Block, FullTerm};
+use crate::{partition_point, Block, FullTerm};
 
-const TERM_SIZE: usize = 32; // 8 + 16 + 8 bytes
+pub(crate) const TERM_SIZE: usize = 32; // 8 + 16 + 8 bytes
+
+/// Frequency that marks a term as deleted; reserved, so no live term has it
+pub const TOMBSTONE_FREQUENCY: u64 = u64::MAX;
 
 /// Serialize a block to bytes using manual zero-copy layout
 pub fn serialize(block: &Block) -> Vec<u8> {
@@ -35,6 +50,73 @@ pub fn serialize(block: &Block) -> Vec<u8> {
     bytes
 }
 
+/// Streaming writer that appends a block to a buffer one term at a time,
+/// without materializing a [`Block`]
+///
+/// The term count is written as a placeholder and back-patched by
+/// [`BlockWriter::finish`]; a writer dropped without finishing leaves a block
+/// that reads as empty.
+pub struct BlockWriter<'a> {
+    bytes: &'a mut Vec<u8>,
+    start: usize,
+    num_terms: usize,
+}
+
+impl<'a> BlockWriter<'a> {
+    /// Start a block at the end of `bytes`
+    pub fn new(bytes: &'a mut Vec<u8>) -> Self {
+        let start = bytes.len();
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+
+        BlockWriter {
+            bytes,
+            start,
+            num_terms: 0,
+        }
+    }
+
+    /// Number of terms written so far
+    pub fn len(&self) -> usize {
+        self.num_terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    /// Append a term, failing if its frequency is [`TOMBSTONE_FREQUENCY`]
+    #[inline]
+    pub fn push(&mut self, term: &FullTerm) -> Result<(), &'static str> {
+        if term.frequency == TOMBSTONE_FREQUENCY {
+            return Err("Frequency is reserved for tombstones");
+        }
+
+        self.bytes.extend_from_slice(&term.doc_id.to_le_bytes());
+        self.bytes.extend_from_slice(&term.field_mask.to_le_bytes());
+        self.bytes.extend_from_slice(&term.frequency.to_le_bytes());
+        self.num_terms += 1;
+
+        Ok(())
+    }
+
+    /// Append a term from another block by copying its bytes, without
+    /// decoding it
+    #[inline]
+    pub fn push_raw(&mut self, term: &TermReader) {
+        self.bytes
+            .extend_from_slice(&term.bytes[term.offset..term.offset + TERM_SIZE]);
+        self.num_terms += 1;
+    }
+
+    /// Back-patch the term count, returning the size of the block in bytes
+    pub fn finish(self) -> usize {
+        self.bytes[self.start..self.start + 4]
+            .copy_from_slice(&(self.num_terms as u32).to_le_bytes());
+
+        self.bytes.len() - self.start
+    }
+}
+
 /// Deserialize a block from bytes (full deserialization)
 pub fn deserialize(bytes: &[u8]) -> Result<Block, &'static str> {
     if bytes.len() < 4 {
@@ -102,6 +184,19 @@ impl<'a> BlockReader<'a> {
         self.num_terms
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    /// Read the term at `index` without iterating up to it
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<TermReader<'a>> {
+        (index < self.num_terms).then(|| TermReader {
+            bytes: self.bytes,
+            offset: 4 + index * TERM_SIZE,
+        })
+    }
+
     pub fn iter(&self) -> TermIterator<'a> {
         TermIterator {
             bytes: self.bytes,
@@ -109,6 +204,22 @@ impl<'a> BlockReader<'a> {
             remaining: self.num_terms,
         }
     }
+
+    /// Iterate over the terms with a doc_id in `doc_ids`, binary-searching for
+    /// the bounds
+    ///
+    /// Doc ids must be sorted in ascending order, as they are in every block.
+    pub fn range(&self, doc_ids: Range<u64>) -> TermIterator<'a> {
+        let doc_id = |index| self.get(index).unwrap().doc_id();
+        let start = partition_point(self.num_terms, |index| doc_id(index) < doc_ids.start);
+        let end = partition_point(self.num_terms, |index| doc_id(index) < doc_ids.end);
+
+        TermIterator {
+            bytes: self.bytes,
+            offset: 4 + start * TERM_SIZE,
+            remaining: end.saturating_sub(start),
+        }
+    }
 }
 
 /// Iterator over terms in a block (zero-copy)
@@ -191,6 +302,12 @@ impl<'a> TermReader<'a> {
         }
     }
 
+    /// Whether this term was deleted with [`TermMut::tombstone`]
+    #[inline(always)]
+    pub fn is_tombstone(&self) -> bool {
+        self.frequency() == TOMBSTONE_FREQUENCY
+    }
+
     /// Fully deserialize this term
     pub fn deserialize(&self) -> FullTerm {
         FullTerm {
@@ -201,6 +318,123 @@ impl<'a> TermReader<'a> {
     }
 }
 
+/// Mutable access to the terms of a serialized block, for updating them in
+/// place without re-encoding the block
+pub struct BlockWriterMut<'a> {
+    terms: &'a mut [u8],
+}
+
+impl<'a> BlockWriterMut<'a> {
+    pub fn new(bytes: &'a mut [u8]) -> Result<Self, &'static str> {
+        let num_terms = BlockReader::new(bytes)?.len();
+
+        Ok(BlockWriterMut {
+            terms: &mut bytes[4..4 + num_terms * TERM_SIZE],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len() / TERM_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Mutable access to the term at `index`
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<TermMut<'_>> {
+        let offset = index * TERM_SIZE;
+        self.terms
+            .get_mut(offset..offset + TERM_SIZE)
+            .map(|bytes| TermMut { bytes })
+    }
+
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = TermMut<'_>> {
+        self.terms
+            .chunks_exact_mut(TERM_SIZE)
+            .map(|bytes| TermMut { bytes })
+    }
+
+    /// Index of the first term with `doc_id`, found by binary search
+    ///
+    /// Doc ids must be sorted in ascending order, as they are in every block.
+    pub fn position(&self, doc_id: u64) -> Option<usize> {
+        let read_doc_id = |index: usize| {
+            let offset = index * TERM_SIZE;
+            u64::from_le_bytes(self.terms[offset..offset + 8].try_into().unwrap())
+        };
+
+        let index = partition_point(self.len(), |index| read_doc_id(index) < doc_id);
+        (index < self.len() && read_doc_id(index) == doc_id).then_some(index)
+    }
+
+    /// Delete the term at `index`, returning false if it is out of bounds
+    pub fn tombstone(&mut self, index: usize) -> bool {
+        self.get_mut(index)
+            .map(|mut term| term.tombstone())
+            .is_some()
+    }
+}
+
+/// Mutable view of a single serialized term
+pub struct TermMut<'a> {
+    bytes: &'a mut [u8],
+}
+
+impl<'a> TermMut<'a> {
+    #[inline(always)]
+    pub fn doc_id(&self) -> u64 {
+        u64::from_le_bytes(self.bytes[0..8].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn field_mask(&self) -> u128 {
+        u128::from_le_bytes(self.bytes[8..24].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn frequency(&self) -> u64 {
+        u64::from_le_bytes(self.bytes[24..32].try_into().unwrap())
+    }
+
+    /// Overwrite doc_id; keeping the block sorted is up to the caller
+    #[inline(always)]
+    pub fn set_doc_id(&mut self, doc_id: u64) {
+        self.bytes[0..8].copy_from_slice(&doc_id.to_le_bytes());
+    }
+
+    #[inline(always)]
+    pub fn set_field_mask(&mut self, field_mask: u128) {
+        self.bytes[8..24].copy_from_slice(&field_mask.to_le_bytes());
+    }
+
+    /// Overwrite frequency, failing if it is [`TOMBSTONE_FREQUENCY`]; use
+    /// [`TermMut::tombstone`] to delete the term
+    #[inline(always)]
+    pub fn set_frequency(&mut self, frequency: u64) -> Result<(), &'static str> {
+        if frequency == TOMBSTONE_FREQUENCY {
+            return Err("Frequency is reserved for tombstones");
+        }
+
+        self.bytes[24..32].copy_from_slice(&frequency.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Mark the term as deleted, keeping its doc_id so the block stays sorted
+    #[inline]
+    pub fn tombstone(&mut self) {
+        self.set_field_mask(0);
+        self.bytes[24..32].copy_from_slice(&TOMBSTONE_FREQUENCY.to_le_bytes());
+    }
+
+    #[inline(always)]
+    pub fn is_tombstone(&self) -> bool {
+        self.frequency() == TOMBSTONE_FREQUENCY
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,4 +490,190 @@ mod tests {
         assert_eq!(term.field_mask(), 0xFF00FF00);
         assert_eq!(term.frequency(), 7);
     }
+
+    const RANGES: [(u64, u64, &[u64]); 6] = [
+        (0, 3, &[2]),
+        (4, 5, &[4, 4]),
+        (5, 7, &[]),
+        (3, 100, &[4, 4, 7, 9]),
+        (9, 2, &[]),
+        (10, 20, &[]),
+    ];
+
+    fn range_block() -> Block {
+        Block {
+            full_terms: [2, 4, 4, 7, 9]
+                .into_iter()
+                .map(|doc_id| FullTerm {
+                    doc_id,
+                    field_mask: 1,
+                    frequency: doc_id * 10,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let block = range_block();
+        let bytes = serialize(&block);
+        let reader = BlockReader::new(&bytes).unwrap();
+
+        let archived = rkyv::to_bytes::<rkyv::rancor::Error>(&block).unwrap();
+        let archived =
+            rkyv::access::<crate::ArchivedBlock, rkyv::rancor::Error>(&archived).unwrap();
+
+        for (start, end, expected) in RANGES {
+            let terms = reader.range(start..end);
+            assert_eq!(terms.len(), expected.len());
+            let doc_ids: Vec<u64> = terms.map(|term| term.doc_id()).collect();
+            assert_eq!(doc_ids, expected, "range {start}..{end}");
+
+            let doc_ids: Vec<u64> = archived
+                .range(start..end)
+                .iter()
+                .map(|term| term.doc_id.to_native())
+                .collect();
+            assert_eq!(doc_ids, expected, "rkyv range {start}..{end}");
+        }
+    }
+
+    #[test]
+    fn test_block_writer() {
+        let blocks: Vec<Block> = crate::generate_test_data().into_iter().take(3).collect();
+
+        let mut bytes = Vec::new();
+        let mut sizes = Vec::new();
+        for block in &blocks {
+            let mut writer = BlockWriter::new(&mut bytes);
+            for term in &block.full_terms {
+                writer.push(term).unwrap();
+            }
+            assert_eq!(writer.len(), block.full_terms.len());
+            sizes.push(writer.finish());
+        }
+
+        // Consecutive blocks in one buffer match the one-shot serializer
+        let mut offset = 0;
+        for (block, size) in blocks.iter().zip(sizes) {
+            assert_eq!(&bytes[offset..offset + size], serialize(block));
+            offset += size;
+        }
+        assert_eq!(offset, bytes.len());
+
+        let mut bytes = Vec::new();
+        assert_eq!(BlockWriter::new(&mut bytes).finish(), 4);
+        assert!(BlockReader::new(&bytes).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_in_place_update() {
+        let block = Block {
+            full_terms: (0..5)
+                .map(|i| FullTerm {
+                    doc_id: i * 10,
+                    field_mask: 1 << i,
+                    frequency: i + 1,
+                })
+                .collect(),
+        };
+        let mut bytes = serialize(&block);
+
+        let mut writer = BlockWriterMut::new(&mut bytes).unwrap();
+        assert_eq!(writer.len(), 5);
+        assert_eq!(writer.position(30), Some(3));
+        assert_eq!(writer.position(35), None);
+
+        let mut term = writer.get_mut(1).unwrap();
+        term.set_frequency(99).unwrap();
+        assert!(term.set_frequency(TOMBSTONE_FREQUENCY).is_err());
+        term.set_field_mask(1 << 127);
+        assert!(writer.tombstone(3));
+        assert!(!writer.tombstone(5));
+        for mut term in writer.iter_mut() {
+            term.set_doc_id(term.doc_id() + 1);
+        }
+
+        let reader = BlockReader::new(&bytes).unwrap();
+        let terms: Vec<FullTerm> = reader.iter().map(|term| term.deserialize()).collect();
+        assert_eq!(terms[1].frequency, 99);
+        assert_eq!(terms[1].field_mask, 1 << 127);
+        assert_eq!(terms[4].doc_id, 41);
+
+        let live: Vec<u64> = reader
+            .iter()
+            .filter(|term| !term.is_tombstone())
+            .map(|term| term.doc_id())
+            .collect();
+        assert_eq!(live, [1, 11, 21, 41]);
+        assert_eq!(reader.get(3).unwrap().field_mask(), 0);
+    }
+
+    #[test]
+    fn test_zero_frequency_is_live() {
+        let block = Block {
+            full_terms: vec![FullTerm {
+                doc_id: 7,
+                field_mask: 0,
+                frequency: 0,
+            }],
+        };
+        let mut bytes = serialize(&block);
+
+        let term = BlockReader::new(&bytes).unwrap().get(0).unwrap();
+        assert!(!term.is_tombstone());
+        assert_eq!(term.frequency(), 0);
+        assert_eq!(deserialize(&bytes).unwrap().full_terms[0].frequency, 0);
+
+        let mut writer = BlockWriterMut::new(&mut bytes).unwrap();
+        assert!(!writer.get_mut(0).unwrap().is_tombstone());
+        assert!(writer.tombstone(0));
+        assert!(BlockReader::new(&bytes)
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .is_tombstone());
+    }
+
+    #[test]
+    fn test_reserved_frequency() {
+        let mut bytes = Vec::new();
+        let mut writer = BlockWriter::new(&mut bytes);
+        let term = FullTerm {
+            doc_id: 0,
+            field_mask: 0,
+            frequency: TOMBSTONE_FREQUENCY,
+        };
+        assert!(writer.push(&term).is_err());
+        assert_eq!(writer.finish(), 4);
+    }
+
+    #[test]
+    fn test_tombstone_roundtrip() {
+        let block = crate::generate_test_data().swap_remove(0);
+        let mut bytes = serialize(&block);
+        BlockWriterMut::new(&mut bytes).unwrap().tombstone(2);
+
+        // A decoded tombstone keeps its marker and re-encodes to the same bytes
+        let decoded = deserialize(&bytes).unwrap();
+        assert_eq!(decoded.full_terms.len(), block.full_terms.len());
+        assert_eq!(decoded.full_terms[2].frequency, TOMBSTONE_FREQUENCY);
+        assert_eq!(serialize(&decoded), bytes);
+
+        let reader = BlockReader::new(&bytes).unwrap();
+        assert!(reader.get(2).unwrap().is_tombstone());
+        let live_frequency: u64 = reader
+            .iter()
+            .filter(|term| !term.is_tombstone())
+            .map(|term| term.frequency())
+            .sum();
+        let expected: u64 = block
+            .full_terms
+            .iter()
+            .enumerate()
+            .filter(|&(position, _)| position != 2)
+            .map(|(_, term)| term.frequency)
+            .sum();
+        assert_eq!(live_frequency, expected);
+    }
 }
@@ -3,7 +3,9 @@
 //! This version uses direct references to byte arrays representing the fields,
 //! similar to rkyv's archived types. No offset calculations needed during access.
 
-use crate::{Block, FullTerm};
+use std::ops::Range;
+
+use crate::{partition_point, Block, FullTerm};
 
 const TERM_SIZE: usize = 32; // 8 + 16 + 8 bytes
 
@@ -121,6 +123,20 @@ impl<'a> BlockReader<'a> {
         self.num_terms
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.num_terms == 0
+    }
+
+    /// Read the term at `index` without iterating up to it
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<ArchivedFullTerm<'a>> {
+        // SAFETY: the buffer size was validated in BlockReader::new() and
+        // `index` is in bounds, so the term's bytes are valid
+        (index < self.num_terms).then(|| unsafe {
+            ArchivedFullTerm::from_bytes_unchecked(self.bytes, 4 + index * TERM_SIZE)
+        })
+    }
+
     pub fn iter(&self) -> TermIterator<'a> {
         TermIterator {
             bytes: self.bytes,
@@ -128,6 +144,22 @@ impl<'a> BlockReader<'a> {
             remaining: self.num_terms,
         }
     }
+
+    /// Iterate over the terms with a doc_id in `doc_ids`, binary-searching for
+    /// the bounds
+    ///
+    /// Doc ids must be sorted in ascending order, as they are in every block.
+    pub fn range(&self, doc_ids: Range<u64>) -> TermIterator<'a> {
+        let doc_id = |index| self.get(index).unwrap().doc_id();
+        let start = partition_point(self.num_terms, |index| doc_id(index) < doc_ids.start);
+        let end = partition_point(self.num_terms, |index| doc_id(index) < doc_ids.end);
+
+        TermIterator {
+            bytes: self.bytes,
+            offset: 4 + start * TERM_SIZE,
+            remaining: end.saturating_sub(start),
+        }
+    }
 }
 
 /// Iterator over terms in a block (zero-copy)
@@ -218,4 +250,37 @@ mod tests {
         assert_eq!(term.field_mask(), 0xFF00FF00);
         assert_eq!(term.frequency(), 7);
     }
+
+    const RANGES: [(u64, u64, &[u64]); 6] = [
+        (0, 3, &[2]),
+        (4, 5, &[4, 4]),
+        (5, 7, &[]),
+        (3, 100, &[4, 4, 7, 9]),
+        (9, 2, &[]),
+        (10, 20, &[]),
+    ];
+
+    fn range_block() -> Block {
+        Block {
+            full_terms: [2, 4, 4, 7, 9]
+                .into_iter()
+                .map(|doc_id| FullTerm {
+                    doc_id,
+                    field_mask: 1,
+                    frequency: doc_id * 10,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let bytes = serialize(&range_block());
+        let reader = BlockReader::new(&bytes).unwrap();
+
+        for (start, end, expected) in RANGES {
+            let doc_ids: Vec<u64> = reader.range(start..end).map(|term| term.doc_id()).collect();
+            assert_eq!(doc_ids, expected, "range {start}..{end}");
+        }
+    }
 }
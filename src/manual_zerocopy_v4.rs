@@ -0,0 +1,213 @@
+//! Manual zero-copy serialization implementation V4
+//!
+//! Same byte layout as v1/v2, but the term region is reinterpreted as a
+//! `&[TermLe]` in a single checked cast using `zerocopy`. `TermLe` only holds
+//! little-endian byte-order wrappers, which have an alignment of 1, so the cast
+//! is sound for buffers at any address (the terms start at offset 4).
+
+use zerocopy::little_endian::{U128, U32, U64};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+use crate::{Block, FullTerm};
+
+const HEADER_SIZE: usize = 4; // u32 number of terms
+
+/// Little-endian on-disk representation of a FullTerm
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct TermLe {
+    doc_id: U64,
+    field_mask: U128,
+    frequency: U64,
+}
+
+const TERM_SIZE: usize = size_of::<TermLe>();
+
+impl TermLe {
+    /// Access doc_id
+    #[inline(always)]
+    pub fn doc_id(&self) -> u64 {
+        self.doc_id.get()
+    }
+
+    /// Access field_mask
+    #[inline(always)]
+    pub fn field_mask(&self) -> u128 {
+        self.field_mask.get()
+    }
+
+    /// Access frequency
+    #[inline(always)]
+    pub fn frequency(&self) -> u64 {
+        self.frequency.get()
+    }
+
+    /// Deserialize into a FullTerm
+    #[inline]
+    pub fn deserialize(&self) -> FullTerm {
+        FullTerm {
+            doc_id: self.doc_id(),
+            field_mask: self.field_mask(),
+            frequency: self.frequency(),
+        }
+    }
+}
+
+impl From<&FullTerm> for TermLe {
+    fn from(term: &FullTerm) -> Self {
+        TermLe {
+            doc_id: U64::new(term.doc_id),
+            field_mask: U128::new(term.field_mask),
+            frequency: U64::new(term.frequency),
+        }
+    }
+}
+
+/// Serialize a block to bytes using manual zero-copy layout
+pub fn serialize(block: &Block) -> Vec<u8> {
+    let num_terms = block.full_terms.len();
+    let total_size = HEADER_SIZE + (num_terms * TERM_SIZE);
+
+    let mut bytes = Vec::with_capacity(total_size);
+
+    // Write number of terms as u32 little-endian
+    bytes.extend_from_slice(U32::new(num_terms as u32).as_bytes());
+
+    // Write each term
+    for term in &block.full_terms {
+        bytes.extend_from_slice(TermLe::from(term).as_bytes());
+    }
+
+    bytes
+}
+
+/// Deserialize a block from bytes (full deserialization through the cast terms)
+pub fn deserialize(bytes: &[u8]) -> Result<Block, &'static str> {
+    let reader = BlockReader::new(bytes)?;
+
+    let full_terms = reader.iter().map(TermLe::deserialize).collect();
+
+    Ok(Block { full_terms })
+}
+
+/// Zero-copy reader for accessing block data without full deserialization
+pub struct BlockReader<'a> {
+    terms: &'a [TermLe],
+}
+
+impl<'a> BlockReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, &'static str> {
+        let (num_terms, rest) =
+            U32::ref_from_prefix(bytes).map_err(|_| "Buffer too small for header")?;
+
+        let (terms, _) = <[TermLe]>::ref_from_prefix_with_elems(rest, num_terms.get() as usize)
+            .map_err(|_| "Buffer too small for data")?;
+
+        Ok(BlockReader { terms })
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// All terms in the block as a slice
+    pub fn terms(&self) -> &'a [TermLe] {
+        self.terms
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'a, TermLe> {
+        self.terms.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let block = Block {
+            full_terms: vec![
+                FullTerm {
+                    doc_id: 1,
+                    field_mask: 0xDEADBEEF,
+                    frequency: 42,
+                },
+                FullTerm {
+                    doc_id: 2,
+                    field_mask: 0xCAFEBABE,
+                    frequency: 123,
+                },
+            ],
+        };
+
+        let bytes = serialize(&block);
+        let deserialized = deserialize(&bytes).unwrap();
+
+        assert_eq!(block.full_terms.len(), deserialized.full_terms.len());
+        assert_eq!(
+            block.full_terms[0].doc_id,
+            deserialized.full_terms[0].doc_id
+        );
+        assert_eq!(
+            block.full_terms[1].frequency,
+            deserialized.full_terms[1].frequency
+        );
+    }
+
+    #[test]
+    fn test_same_layout_as_v1() {
+        let block = Block {
+            full_terms: vec![FullTerm {
+                doc_id: 100,
+                field_mask: (0xFF00FF00 << 64) | 0x1234,
+                frequency: 7,
+            }],
+        };
+
+        assert_eq!(serialize(&block), crate::manual_zerocopy::serialize(&block));
+    }
+
+    #[test]
+    fn test_unaligned_buffer() {
+        let block = Block {
+            full_terms: vec![FullTerm {
+                doc_id: 100,
+                field_mask: 0xFF00FF00,
+                frequency: 7,
+            }],
+        };
+
+        // Shift the serialized block by one byte so nothing is 8-byte aligned
+        let mut shifted = vec![0u8];
+        shifted.extend_from_slice(&serialize(&block));
+
+        let reader = BlockReader::new(&shifted[1..]).unwrap();
+        assert_eq!(reader.len(), 1);
+
+        let term = reader.iter().next().unwrap();
+        assert_eq!(term.doc_id(), 100);
+        assert_eq!(term.field_mask(), 0xFF00FF00);
+        assert_eq!(term.frequency(), 7);
+    }
+
+    #[test]
+    fn test_truncated_buffer() {
+        let block = Block {
+            full_terms: vec![FullTerm {
+                doc_id: 100,
+                field_mask: 0xFF00FF00,
+                frequency: 7,
+            }],
+        };
+
+        let bytes = serialize(&block);
+
+        assert!(BlockReader::new(&bytes[..2]).is_err());
+        assert!(BlockReader::new(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,533 @@
+//! Merging segments of serialized blocks into one
+//!
+//! A [`Segment`] is a sequence of serialized blocks whose doc ids increase
+//! within and across blocks, optionally with the [`LiveDocs`] of each block.
+//! [`merge`] combines several segments into one: it merges their terms by doc
+//! id, drops deleted terms and rebuilds blocks of `block_size` terms, so only
+//! the last block can be short. Input blocks are decoded with one codec and
+//! output blocks encoded with another, so a merge can also convert between
+//! codecs.
+//!
+//! [`merge_manual_zerocopy`] is the fast path for the [`manual_zerocopy`]
+//! layout: it compares doc ids in place and copies each surviving term's bytes
+//! into the output without decoding it.
+//!
+//! A term is deleted when its block's live docs say so. Tombstones are a
+//! [`manual_zerocopy`] marker, so only [`merge_manual_zerocopy`] drops them;
+//! [`merge`] keeps every term its live docs keep, whatever its frequency.
+//! Terms with the same doc id in several segments are all kept, in segment
+//! order.
+
+use crate::live_docs::LiveDocs;
+use crate::manual_zerocopy::{self, BlockWriter};
+use crate::{Block, FullTerm};
+
+/// Serialized blocks sorted by doc id, with optional per-block deletions
+pub struct Segment<'a, B> {
+    pub blocks: &'a [B],
+    /// Live docs of each block, or `None` if nothing was deleted
+    pub live_docs: Option<&'a [LiveDocs]>,
+}
+
+impl<'a, B> Segment<'a, B> {
+    /// Segment with every term live
+    pub fn new(blocks: &'a [B]) -> Self {
+        Segment {
+            blocks,
+            live_docs: None,
+        }
+    }
+
+    /// Segment with the deletions of `live_docs`, one per block
+    pub fn with_live_docs(blocks: &'a [B], live_docs: &'a [LiveDocs]) -> Self {
+        Segment {
+            blocks,
+            live_docs: Some(live_docs),
+        }
+    }
+
+    /// Live docs of the block at `index`, checked against its term count
+    fn block_live_docs(
+        &self,
+        index: usize,
+        num_terms: usize,
+    ) -> Result<Option<&'a LiveDocs>, &'static str> {
+        let Some(live_docs) = self.live_docs else {
+            return Ok(None);
+        };
+
+        match live_docs.get(index) {
+            Some(block_live_docs) if block_live_docs.len() == num_terms => {
+                Ok(Some(block_live_docs))
+            }
+            _ => Err("Live docs don't match the segment's blocks"),
+        }
+    }
+}
+
+impl<B> Clone for Segment<'_, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B> Copy for Segment<'_, B> {}
+
+#[inline(always)]
+fn is_live(live_docs: Option<&LiveDocs>, position: usize) -> bool {
+    live_docs.is_none_or(|live_docs| live_docs.is_live(position))
+}
+
+/// Index of the cursor with the lowest doc id, the first one on ties, or
+/// `None` once every cursor is exhausted
+///
+/// A linear scan beats a heap for the handful of segments a merge takes.
+#[inline]
+fn lowest(doc_ids: impl Iterator<Item = Option<u64>>) -> Option<usize> {
+    doc_ids
+        .enumerate()
+        .filter_map(|(index, doc_id)| Some((doc_id?, index)))
+        .min()
+        .map(|(_, index)| index)
+}
+
+/// Position at the next live term of a segment, decoding one block at a time
+struct DecodedCursor<'a, B> {
+    segment: Segment<'a, B>,
+    next_block: usize,
+    live_docs: Option<&'a LiveDocs>,
+    terms: Vec<FullTerm>,
+    position: usize,
+}
+
+impl<'a, B: AsRef<[u8]>> DecodedCursor<'a, B> {
+    fn new(
+        segment: Segment<'a, B>,
+        decode: &impl Fn(&[u8]) -> Result<Block, &'static str>,
+    ) -> Result<Self, &'static str> {
+        let mut cursor = DecodedCursor {
+            segment,
+            next_block: 0,
+            live_docs: None,
+            terms: Vec::new(),
+            position: 0,
+        };
+        cursor.skip_deleted(decode)?;
+
+        Ok(cursor)
+    }
+
+    fn current(&self) -> Option<&FullTerm> {
+        self.terms.get(self.position)
+    }
+
+    fn advance(
+        &mut self,
+        decode: &impl Fn(&[u8]) -> Result<Block, &'static str>,
+    ) -> Result<(), &'static str> {
+        self.position += 1;
+        self.skip_deleted(decode)
+    }
+
+    /// Move to the first live term at or after the current position
+    fn skip_deleted(
+        &mut self,
+        decode: &impl Fn(&[u8]) -> Result<Block, &'static str>,
+    ) -> Result<(), &'static str> {
+        loop {
+            while self.position < self.terms.len() {
+                if is_live(self.live_docs, self.position) {
+                    return Ok(());
+                }
+                self.position += 1;
+            }
+
+            let Some(bytes) = self.segment.blocks.get(self.next_block) else {
+                return Ok(());
+            };
+
+            self.terms = decode(bytes.as_ref())?.full_terms;
+            self.live_docs = self
+                .segment
+                .block_live_docs(self.next_block, self.terms.len())?;
+            self.next_block += 1;
+            self.position = 0;
+        }
+    }
+}
+
+/// Merge `segments` into blocks of `block_size` live terms, reading blocks
+/// with `decode` and writing them with `encode`
+pub fn merge<B: AsRef<[u8]>>(
+    segments: &[Segment<B>],
+    block_size: usize,
+    decode: impl Fn(&[u8]) -> Result<Block, &'static str>,
+    encode: impl Fn(&Block) -> Vec<u8>,
+) -> Result<Vec<Vec<u8>>, &'static str> {
+    if block_size == 0 {
+        return Err("Block size must be at least 1");
+    }
+
+    let mut cursors = segments
+        .iter()
+        .map(|&segment| DecodedCursor::new(segment, &decode))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut merged = Vec::new();
+    let mut block = Block {
+        full_terms: Vec::with_capacity(block_size),
+    };
+
+    while let Some(index) = lowest(cursors.iter().map(|cursor| Some(cursor.current()?.doc_id))) {
+        // `lowest` only picks cursors that have a term
+        block
+            .full_terms
+            .push(cursors[index].current().unwrap().clone());
+        cursors[index].advance(&decode)?;
+
+        if block.full_terms.len() == block_size {
+            merged.push(encode(&block));
+            block.full_terms.clear();
+        }
+    }
+
+    if !block.full_terms.is_empty() {
+        merged.push(encode(&block));
+    }
+
+    Ok(merged)
+}
+
+/// Position at the next live term of a [`manual_zerocopy`] segment, read in
+/// place
+struct ManualCursor<'a, B> {
+    segment: Segment<'a, B>,
+    next_block: usize,
+    live_docs: Option<&'a LiveDocs>,
+    reader: Option<manual_zerocopy::BlockReader<'a>>,
+    position: usize,
+}
+
+impl<'a, B: AsRef<[u8]>> ManualCursor<'a, B> {
+    fn new(segment: Segment<'a, B>) -> Result<Self, &'static str> {
+        let mut cursor = ManualCursor {
+            segment,
+            next_block: 0,
+            live_docs: None,
+            reader: None,
+            position: 0,
+        };
+        cursor.skip_deleted()?;
+
+        Ok(cursor)
+    }
+
+    #[inline]
+    fn current(&self) -> Option<manual_zerocopy::TermReader<'a>> {
+        self.reader.as_ref()?.get(self.position)
+    }
+
+    fn advance(&mut self) -> Result<(), &'static str> {
+        self.position += 1;
+        self.skip_deleted()
+    }
+
+    /// Move to the first live term at or after the current position
+    fn skip_deleted(&mut self) -> Result<(), &'static str> {
+        loop {
+            while let Some(term) = self.current() {
+                if !term.is_tombstone() && is_live(self.live_docs, self.position) {
+                    return Ok(());
+                }
+                self.position += 1;
+            }
+
+            let Some(bytes) = self.segment.blocks.get(self.next_block) else {
+                return Ok(());
+            };
+
+            let reader = manual_zerocopy::BlockReader::new(bytes.as_ref())?;
+            self.live_docs = self
+                .segment
+                .block_live_docs(self.next_block, reader.len())?;
+            self.reader = Some(reader);
+            self.next_block += 1;
+            self.position = 0;
+        }
+    }
+}
+
+/// Merge [`manual_zerocopy`] segments into blocks of `block_size` live terms
+/// of the same layout, copying terms without decoding them
+///
+/// Produces the same bytes as [`merge`] with [`manual_zerocopy::deserialize`]
+/// and [`manual_zerocopy::serialize`], except that tombstones are dropped too.
+pub fn merge_manual_zerocopy<B: AsRef<[u8]>>(
+    segments: &[Segment<B>],
+    block_size: usize,
+) -> Result<Vec<Vec<u8>>, &'static str> {
+    if block_size == 0 {
+        return Err("Block size must be at least 1");
+    }
+
+    let mut cursors = segments
+        .iter()
+        .map(|&segment| ManualCursor::new(segment))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut merged = Vec::new();
+
+    loop {
+        let mut bytes = Vec::with_capacity(4 + block_size * manual_zerocopy::TERM_SIZE);
+        let mut writer = BlockWriter::new(&mut bytes);
+
+        while writer.len() < block_size {
+            let Some(index) = lowest(
+                cursors
+                    .iter()
+                    .map(|cursor| Some(cursor.current()?.doc_id())),
+            ) else {
+                break;
+            };
+
+            // `lowest` only picks cursors that have a term
+            writer.push_raw(&cursors[index].current().unwrap());
+            cursors[index].advance()?;
+        }
+
+        if writer.is_empty() {
+            break;
+        }
+
+        writer.finish();
+        merged.push(bytes);
+    }
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_test_data, manual_bitpacked, manual_zerocopy_v4};
+
+    /// Deal the terms of a few test blocks round-robin into `num_segments`
+    /// segments with short blocks, deleting every 7th term of each
+    fn test_segments(num_segments: usize) -> (Vec<Vec<Block>>, Vec<Vec<LiveDocs>>) {
+        let terms: Vec<FullTerm> = generate_test_data()
+            .into_iter()
+            .take(10)
+            .flat_map(|block| block.full_terms)
+            .collect();
+
+        let segments: Vec<Vec<Block>> = (0..num_segments)
+            .map(|segment| {
+                let segment_terms: Vec<FullTerm> = terms
+                    .iter()
+                    .skip(segment)
+                    .step_by(num_segments)
+                    .cloned()
+                    .collect();
+
+                segment_terms
+                    .chunks(37)
+                    .map(|chunk| Block {
+                        full_terms: chunk.to_vec(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let live_docs = segments
+            .iter()
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .map(|block| {
+                        let mut live_docs = LiveDocs::new(block.full_terms.len());
+                        for position in (0..block.full_terms.len()).step_by(7) {
+                            live_docs.delete(position);
+                        }
+                        live_docs
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (segments, live_docs)
+    }
+
+    /// Segments over serialized blocks with their live docs
+    fn to_segments<'a>(
+        serialized: &'a [Vec<Vec<u8>>],
+        live_docs: &'a [Vec<LiveDocs>],
+    ) -> Vec<Segment<'a, Vec<u8>>> {
+        serialized
+            .iter()
+            .zip(live_docs)
+            .map(|(blocks, live_docs)| Segment::with_live_docs(blocks, live_docs))
+            .collect()
+    }
+
+    fn expected_doc_ids(segments: &[Vec<Block>], live_docs: &[Vec<LiveDocs>]) -> Vec<u64> {
+        let mut doc_ids: Vec<u64> = segments
+            .iter()
+            .zip(live_docs)
+            .flat_map(|(blocks, live_docs)| blocks.iter().zip(live_docs))
+            .flat_map(|(block, live_docs)| live_docs.filter(&block.full_terms))
+            .map(|term| term.doc_id)
+            .collect();
+        doc_ids.sort_unstable();
+
+        doc_ids
+    }
+
+    #[test]
+    fn test_merge() {
+        let (segments, live_docs) = test_segments(3);
+        let expected = expected_doc_ids(&segments, &live_docs);
+
+        let serialized: Vec<Vec<Vec<u8>>> = segments
+            .iter()
+            .map(|blocks| blocks.iter().map(manual_zerocopy_v4::serialize).collect())
+            .collect();
+        let inputs = to_segments(&serialized, &live_docs);
+
+        // v4 in, bitpacked out
+        let merged = merge(
+            &inputs,
+            100,
+            manual_zerocopy_v4::deserialize,
+            manual_bitpacked::serialize,
+        )
+        .unwrap();
+
+        let blocks: Vec<Block> = merged
+            .iter()
+            .map(|bytes| manual_bitpacked::deserialize(bytes).unwrap())
+            .collect();
+        assert_eq!(blocks.len(), expected.len().div_ceil(100));
+        assert!(blocks[..blocks.len() - 1]
+            .iter()
+            .all(|block| block.full_terms.len() == 100));
+
+        let doc_ids: Vec<u64> = blocks
+            .iter()
+            .flat_map(|block| &block.full_terms)
+            .map(|term| term.doc_id)
+            .collect();
+        assert_eq!(doc_ids, expected);
+
+        assert!(merge(
+            &inputs,
+            0,
+            manual_zerocopy_v4::deserialize,
+            manual_bitpacked::serialize
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_max_frequency_survives() {
+        let config = bincode::config::standard();
+        let mut block = generate_test_data().swap_remove(0);
+        block.full_terms[0].frequency = u64::MAX;
+
+        let bytes = [bincode::encode_to_vec(&block, config).unwrap()];
+        let merged = merge(
+            &[Segment::new(&bytes)],
+            100,
+            |bytes| {
+                bincode::decode_from_slice(bytes, config)
+                    .map(|(block, _)| block)
+                    .map_err(|_| "Invalid bincode block")
+            },
+            |block| bincode::encode_to_vec(block, config).unwrap(),
+        )
+        .unwrap();
+
+        let (first, _): (Block, usize) = bincode::decode_from_slice(&merged[0], config).unwrap();
+        assert_eq!(first.full_terms[0].frequency, u64::MAX);
+        let num_terms: usize = merged
+            .iter()
+            .map(|bytes| {
+                let (block, _): (Block, usize) = bincode::decode_from_slice(bytes, config).unwrap();
+                block.full_terms.len()
+            })
+            .sum();
+        assert_eq!(num_terms, block.full_terms.len());
+    }
+
+    #[test]
+    fn test_manual_fast_path() {
+        let (segments, live_docs) = test_segments(4);
+
+        let mut serialized: Vec<Vec<Vec<u8>>> = segments
+            .iter()
+            .map(|blocks| blocks.iter().map(manual_zerocopy::serialize).collect())
+            .collect();
+        let num_terms = |merged: &[Vec<u8>]| -> usize {
+            merged
+                .iter()
+                .map(|bytes| manual_zerocopy::BlockReader::new(bytes).unwrap().len())
+                .sum()
+        };
+        let expected = expected_doc_ids(&segments, &live_docs).len();
+
+        let merged = merge_manual_zerocopy(&to_segments(&serialized, &live_docs), 100).unwrap();
+        assert_eq!(
+            merged,
+            merge(
+                &to_segments(&serialized, &live_docs),
+                100,
+                manual_zerocopy::deserialize,
+                manual_zerocopy::serialize
+            )
+            .unwrap()
+        );
+        assert_eq!(num_terms(&merged), expected);
+
+        // The fast path drops a tombstone written in place like a live-docs
+        // deletion; the generic merge keeps it as an ordinary term
+        manual_zerocopy::BlockWriterMut::new(&mut serialized[0][0])
+            .unwrap()
+            .tombstone(1);
+        let merged = merge_manual_zerocopy(&to_segments(&serialized, &live_docs), 100).unwrap();
+        assert_eq!(num_terms(&merged), expected - 1);
+        let merged = merge(
+            &to_segments(&serialized, &live_docs),
+            100,
+            manual_zerocopy::deserialize,
+            manual_zerocopy::serialize,
+        )
+        .unwrap();
+        assert_eq!(num_terms(&merged), expected);
+
+        // Frequency 0 is an ordinary frequency, not a deletion
+        let mut zero_frequency = segments[0][0].clone();
+        for term in &mut zero_frequency.full_terms {
+            term.frequency = 0;
+        }
+        let bytes = [manual_zerocopy::serialize(&zero_frequency)];
+        let merged = merge_manual_zerocopy(&[Segment::new(&bytes)], 100).unwrap();
+        let block = manual_zerocopy::deserialize(&merged[0]).unwrap();
+        assert_eq!(block.full_terms.len(), zero_frequency.full_terms.len());
+        assert!(block.full_terms.iter().all(|term| term.frequency == 0));
+        assert_eq!(
+            merged,
+            merge(
+                &[Segment::new(&bytes)],
+                100,
+                manual_zerocopy::deserialize,
+                manual_zerocopy::serialize
+            )
+            .unwrap()
+        );
+
+        let unmatched = [Segment::with_live_docs(
+            &serialized[0][..],
+            &live_docs[1][..1],
+        )];
+        assert!(merge_manual_zerocopy(&unmatched, 100).is_err());
+    }
+}
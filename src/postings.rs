@@ -0,0 +1,718 @@
+//! Posting-list intersection and union across serialized blocks
+//!
+//! A posting list is a sequence of serialized blocks for one term, with doc ids
+//! increasing within and across blocks. [`intersect`] and [`union`] combine two
+//! or more lists into the AND/OR of their doc ids, summing the frequencies of
+//! every list that contains a doc.
+//!
+//! Blocks are read through a [`PostingsCodec`], which reports a block's
+//! first/last doc id and decodes its doc id and frequency columns. The
+//! intersection only decodes a block once its doc id range reaches the doc it
+//! is looking for, so blocks that fall entirely between two candidate docs are
+//! skipped. That only works for codecs that read the range in place; the
+//! serde-based codecs would have to decode the whole block to learn it, so
+//! they decode every block once and check the range on the decoded columns
+//! ([`PostingsCodec::READS_RANGE_IN_PLACE`]).
+
+use capnp::message::ReaderOptions;
+use prost::Message;
+use speedy::Readable;
+
+use crate::{
+    block_capnp, block_fbs, block_proto, capnp_view, manual_bitpacked, manual_dictionary,
+    manual_elias_fano, manual_roaring, manual_zerocopy, manual_zerocopy_v2, manual_zerocopy_v4,
+    ArchivedBlock, Block,
+};
+
+/// A doc id matched by a query, with its frequencies summed across lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Posting {
+    pub doc_id: u64,
+    pub frequency: u64,
+}
+
+/// Result of combining posting lists
+#[derive(Debug, Default)]
+pub struct Postings {
+    pub postings: Vec<Posting>,
+    /// Blocks whose doc id and frequency columns were decoded
+    pub blocks_decoded: usize,
+    /// Blocks skipped because their doc id range ended before the target
+    pub blocks_skipped: usize,
+}
+
+/// Access to the doc id and frequency columns of a serialized block
+pub trait PostingsCodec {
+    /// Whether [`Self::doc_id_range`] reads the range without decoding the
+    /// block; if not, cursors skip the range check and decode every block
+    const READS_RANGE_IN_PLACE: bool = true;
+
+    /// First and last doc id of a block, or `None` if it is empty
+    ///
+    /// Decodes the whole block unless overridden.
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let mut doc_ids = Vec::new();
+        Self::decode(bytes, &mut doc_ids, &mut Vec::new())?;
+
+        Ok(doc_ids.first().copied().zip(doc_ids.last().copied()))
+    }
+
+    /// Append the doc ids and frequencies of a block to the given columns
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str>;
+}
+
+/// Position in one posting list: the decoded columns of the current block and
+/// the index of the current posting in them
+struct Cursor<'a, C, B> {
+    blocks: &'a [B],
+    next_block: usize,
+    doc_ids: Vec<u64>,
+    frequencies: Vec<u64>,
+    index: usize,
+    codec: std::marker::PhantomData<C>,
+}
+
+impl<'a, C: PostingsCodec, B: AsRef<[u8]>> Cursor<'a, C, B> {
+    fn new(blocks: &'a [B]) -> Self {
+        Cursor {
+            blocks,
+            next_block: 0,
+            doc_ids: Vec::new(),
+            frequencies: Vec::new(),
+            index: 0,
+            codec: std::marker::PhantomData,
+        }
+    }
+
+    fn frequency(&self) -> u64 {
+        self.frequencies[self.index]
+    }
+
+    /// Move to the first posting with a doc id of at least `target`, returning
+    /// that doc id, or `None` once the list is exhausted
+    fn advance_to(
+        &mut self,
+        target: u64,
+        result: &mut Postings,
+    ) -> Result<Option<u64>, &'static str> {
+        loop {
+            if self.doc_ids.last().is_some_and(|&last| last >= target) {
+                self.index += self.doc_ids[self.index..].partition_point(|&doc_id| doc_id < target);
+                return Ok(Some(self.doc_ids[self.index]));
+            }
+
+            // Nothing left in the current block, so find the next block that
+            // can contain `target`
+            loop {
+                let Some(bytes) = self.blocks.get(self.next_block) else {
+                    self.doc_ids.clear();
+                    return Ok(None);
+                };
+                self.next_block += 1;
+
+                if C::READS_RANGE_IN_PLACE {
+                    match C::doc_id_range(bytes.as_ref())? {
+                        None => continue,
+                        Some((_, last)) if last < target => {
+                            result.blocks_skipped += 1;
+                            continue;
+                        }
+                        Some(_) => {}
+                    }
+                }
+
+                self.doc_ids.clear();
+                self.frequencies.clear();
+                C::decode(bytes.as_ref(), &mut self.doc_ids, &mut self.frequencies)?;
+                if self.doc_ids.len() != self.frequencies.len() {
+                    return Err("Column lengths differ");
+                }
+
+                self.index = 0;
+                result.blocks_decoded += 1;
+                break;
+            }
+        }
+    }
+}
+
+/// Doc ids present in every list, with frequencies summed across the lists
+pub fn intersect<C: PostingsCodec, B: AsRef<[u8]>>(
+    lists: &[&[B]],
+) -> Result<Postings, &'static str> {
+    if lists.is_empty() {
+        return Err("No posting lists given");
+    }
+
+    let mut result = Postings::default();
+    let mut cursors: Vec<Cursor<C, B>> = lists.iter().map(|blocks| Cursor::new(blocks)).collect();
+
+    // Move the cursors round-robin to the current candidate doc; a cursor that
+    // lands past it makes its doc the new candidate
+    let mut candidate = 0;
+    let mut agreeing = 0;
+    let mut i = 0;
+
+    while let Some(doc_id) = cursors[i].advance_to(candidate, &mut result)? {
+        if doc_id == candidate {
+            agreeing += 1;
+        } else {
+            candidate = doc_id;
+            agreeing = 1;
+        }
+
+        if agreeing == cursors.len() {
+            result.postings.push(Posting {
+                doc_id: candidate,
+                frequency: cursors.iter().map(Cursor::frequency).sum(),
+            });
+
+            let Some(next) = candidate.checked_add(1) else {
+                break;
+            };
+            candidate = next;
+            agreeing = 0;
+        }
+
+        i = (i + 1) % cursors.len();
+    }
+
+    Ok(result)
+}
+
+/// Doc ids present in any list, with frequencies summed across the lists that
+/// contain them
+///
+/// Every block of every list is decoded, so no blocks are skipped.
+pub fn union<C: PostingsCodec, B: AsRef<[u8]>>(lists: &[&[B]]) -> Result<Postings, &'static str> {
+    if lists.is_empty() {
+        return Err("No posting lists given");
+    }
+
+    let mut result = Postings::default();
+    let mut cursors: Vec<Cursor<C, B>> = lists.iter().map(|blocks| Cursor::new(blocks)).collect();
+    let mut current: Vec<Option<u64>> = Vec::with_capacity(cursors.len());
+
+    for cursor in &mut cursors {
+        current.push(cursor.advance_to(0, &mut result)?);
+    }
+
+    while let Some(doc_id) = current.iter().flatten().copied().min() {
+        let mut frequency = 0;
+
+        for (cursor, current) in cursors.iter_mut().zip(&mut current) {
+            if *current == Some(doc_id) {
+                frequency += cursor.frequency();
+                *current = match doc_id.checked_add(1) {
+                    Some(next) => cursor.advance_to(next, &mut result)?,
+                    None => None,
+                };
+            }
+        }
+
+        result.postings.push(Posting { doc_id, frequency });
+    }
+
+    Ok(result)
+}
+
+fn decode_block(block: &Block, doc_ids: &mut Vec<u64>, frequencies: &mut Vec<u64>) {
+    doc_ids.extend(block.full_terms.iter().map(|term| term.doc_id));
+    frequencies.extend(block.full_terms.iter().map(|term| term.frequency));
+}
+
+/// Implements [`PostingsCodec`] for a codec that can only decode whole blocks
+macro_rules! impl_owned_codec {
+    ($codec:ident, $decode:expr) => {
+        impl PostingsCodec for $codec {
+            const READS_RANGE_IN_PLACE: bool = false;
+
+            fn decode(
+                bytes: &[u8],
+                doc_ids: &mut Vec<u64>,
+                frequencies: &mut Vec<u64>,
+            ) -> Result<(), &'static str> {
+                let decode: fn(&[u8]) -> Result<Block, &'static str> = $decode;
+                decode_block(&decode(bytes)?, doc_ids, frequencies);
+                Ok(())
+            }
+        }
+    };
+}
+
+/// rkyv, read in place through the archived block
+pub struct Rkyv;
+/// bincode with the standard configuration
+pub struct Bincode;
+/// postcard
+pub struct Postcard;
+/// MessagePack via `rmp-serde`
+pub struct MessagePack;
+/// CBOR via `ciborium`
+pub struct Cbor;
+/// bitcode
+pub struct Bitcode;
+/// borsh
+pub struct Borsh;
+/// speedy
+pub struct Speedy;
+/// Cap'n Proto, read in place through [`capnp_view`]
+pub struct Capnp;
+/// FlatBuffers, read in place
+pub struct Flatbuffers;
+/// Protocol Buffers via `prost`
+pub struct Protobuf;
+/// [`manual_zerocopy`]
+pub struct ManualZerocopy;
+/// [`manual_zerocopy_v2`]
+pub struct ManualZerocopyV2;
+/// [`manual_zerocopy_v4`]
+pub struct ManualZerocopyV4;
+/// [`manual_bitpacked`]
+pub struct ManualBitpacked;
+/// [`manual_elias_fano`]
+pub struct ManualEliasFano;
+/// [`manual_dictionary`]
+pub struct ManualDictionary;
+/// [`manual_roaring`]
+pub struct ManualRoaring;
+
+impl PostingsCodec for Rkyv {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let block = rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(bytes)
+            .map_err(|_| "Invalid rkyv block")?;
+
+        Ok(block
+            .full_terms
+            .first()
+            .zip(block.full_terms.last())
+            .map(|(first, last)| (first.doc_id.to_native(), last.doc_id.to_native())))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        let block = rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(bytes)
+            .map_err(|_| "Invalid rkyv block")?;
+
+        doc_ids.extend(block.full_terms.iter().map(|term| term.doc_id.to_native()));
+        frequencies.extend(
+            block
+                .full_terms
+                .iter()
+                .map(|term| term.frequency.to_native()),
+        );
+        Ok(())
+    }
+}
+
+impl_owned_codec!(Bincode, |bytes| {
+    bincode::decode_from_slice(bytes, bincode::config::standard())
+        .map(|(block, _len)| block)
+        .map_err(|_| "Invalid bincode block")
+});
+impl_owned_codec!(Postcard, |bytes| {
+    postcard::from_bytes(bytes).map_err(|_| "Invalid postcard block")
+});
+impl_owned_codec!(MessagePack, |bytes| {
+    rmp_serde::from_slice(bytes).map_err(|_| "Invalid MessagePack block")
+});
+impl_owned_codec!(Cbor, |bytes| {
+    ciborium::from_reader(bytes).map_err(|_| "Invalid CBOR block")
+});
+impl_owned_codec!(Bitcode, |bytes| {
+    bitcode::decode(bytes).map_err(|_| "Invalid bitcode block")
+});
+impl_owned_codec!(Borsh, |bytes| {
+    borsh::from_slice(bytes).map_err(|_| "Invalid borsh block")
+});
+impl_owned_codec!(Speedy, |bytes| {
+    Block::read_from_buffer(bytes).map_err(|_| "Invalid speedy block")
+});
+impl_owned_codec!(Protobuf, |bytes| {
+    block_proto::Block::decode(bytes)
+        .map(|block| Block::from_proto(&block))
+        .map_err(|_| "Invalid protobuf block")
+});
+
+fn read_capnp<T>(
+    bytes: &[u8],
+    read: impl FnOnce(capnp_view::BlockReader) -> T,
+) -> Result<T, &'static str> {
+    let message =
+        capnp::serialize::read_message_from_flat_slice(&mut &bytes[..], ReaderOptions::new())
+            .map_err(|_| "Invalid capnp message")?;
+    let root = message
+        .get_root::<block_capnp::block::Reader>()
+        .map_err(|_| "Invalid capnp block")?;
+    let reader = capnp_view::BlockReader::new(root).map_err(|_| "Invalid capnp block")?;
+
+    Ok(read(reader))
+}
+
+impl PostingsCodec for Capnp {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        read_capnp(bytes, |reader| {
+            let first = reader.get(0)?;
+            let last = reader.get(reader.len() - 1)?;
+            Some((first.doc_id(), last.doc_id()))
+        })
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        read_capnp(bytes, |reader| {
+            for term in reader.iter() {
+                doc_ids.push(term.doc_id());
+                frequencies.push(term.frequency());
+            }
+        })
+    }
+}
+
+impl PostingsCodec for Flatbuffers {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let block = block_fbs::root_as_block(bytes).map_err(|_| "Invalid flatbuffers block")?;
+
+        Ok(block.full_terms().and_then(|terms| {
+            (!terms.is_empty())
+                .then(|| (terms.get(0).doc_id(), terms.get(terms.len() - 1).doc_id()))
+        }))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        let block = block_fbs::root_as_block(bytes).map_err(|_| "Invalid flatbuffers block")?;
+
+        for term in block.full_terms().iter().flatten() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualZerocopy {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let reader = manual_zerocopy::BlockReader::new(bytes)?;
+
+        Ok(reader
+            .get(0)
+            .zip(
+                reader
+                    .len()
+                    .checked_sub(1)
+                    .and_then(|last| reader.get(last)),
+            )
+            .map(|(first, last)| (first.doc_id(), last.doc_id())))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        for term in manual_zerocopy::BlockReader::new(bytes)?.iter() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualZerocopyV2 {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let reader = manual_zerocopy_v2::BlockReader::new(bytes)?;
+
+        Ok(reader
+            .get(0)
+            .zip(
+                reader
+                    .len()
+                    .checked_sub(1)
+                    .and_then(|last| reader.get(last)),
+            )
+            .map(|(first, last)| (first.doc_id(), last.doc_id())))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        for term in manual_zerocopy_v2::BlockReader::new(bytes)?.iter() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualZerocopyV4 {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let terms = manual_zerocopy_v4::BlockReader::new(bytes)?.terms();
+
+        Ok(terms
+            .first()
+            .zip(terms.last())
+            .map(|(first, last)| (first.doc_id(), last.doc_id())))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        for term in manual_zerocopy_v4::BlockReader::new(bytes)?.iter() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualBitpacked {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let reader = manual_bitpacked::BlockReader::new(bytes)?;
+
+        Ok((!reader.is_empty()).then(|| (reader.doc_id(0), reader.doc_id(reader.len() - 1))))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        for term in manual_bitpacked::BlockReader::new(bytes)?.iter() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualEliasFano {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let reader = manual_elias_fano::BlockReader::new(bytes)?;
+
+        Ok(reader.doc_id(0).zip(
+            reader
+                .len()
+                .checked_sub(1)
+                .and_then(|last| reader.doc_id(last)),
+        ))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        for term in manual_elias_fano::BlockReader::new(bytes)?.iter() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualDictionary {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let reader = manual_dictionary::BlockReader::new(bytes)?;
+
+        Ok(reader
+            .len()
+            .checked_sub(1)
+            .map(|last| (reader.doc_id(0), reader.doc_id(last))))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        for term in manual_dictionary::BlockReader::new(bytes)?.iter() {
+            doc_ids.push(term.doc_id());
+            frequencies.push(term.frequency());
+        }
+        Ok(())
+    }
+}
+
+impl PostingsCodec for ManualRoaring {
+    fn doc_id_range(bytes: &[u8]) -> Result<Option<(u64, u64)>, &'static str> {
+        let reader = manual_roaring::BlockReader::new(bytes)?;
+
+        Ok(reader
+            .len()
+            .checked_sub(1)
+            .map(|last| (reader.doc_id(0), reader.doc_id(last))))
+    }
+
+    fn decode(
+        bytes: &[u8],
+        doc_ids: &mut Vec<u64>,
+        frequencies: &mut Vec<u64>,
+    ) -> Result<(), &'static str> {
+        let reader = manual_roaring::BlockReader::new(bytes)?;
+
+        for position in 0..reader.len() {
+            doc_ids.push(reader.doc_id(position));
+            frequencies.push(reader.frequency(position));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_postings, FullTerm};
+
+    fn list(doc_ids: &[u64], frequency: u64) -> Vec<Vec<u8>> {
+        doc_ids
+            .chunks(3)
+            .map(|chunk| {
+                manual_zerocopy::serialize(&Block {
+                    full_terms: chunk
+                        .iter()
+                        .map(|&doc_id| FullTerm {
+                            doc_id,
+                            field_mask: 1,
+                            frequency,
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    fn doc_ids(postings: &Postings) -> Vec<u64> {
+        postings
+            .postings
+            .iter()
+            .map(|posting| posting.doc_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_intersect_and_union() {
+        let a = list(&[1, 2, 3, 5, 8, 13, 21, 34], 1);
+        let b = list(&[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 34], 10);
+        let c = list(&[3, 13, 34, 55], 100);
+
+        let and = intersect::<ManualZerocopy, _>(&[&a, &b, &c]).unwrap();
+        assert_eq!(doc_ids(&and), [3, 13, 34]);
+        assert!(and.postings.iter().all(|posting| posting.frequency == 111));
+
+        let or = union::<ManualZerocopy, _>(&[&a, &c]).unwrap();
+        assert_eq!(doc_ids(&or), [1, 2, 3, 5, 8, 13, 21, 34, 55]);
+        assert_eq!(or.postings[2].frequency, 101);
+        assert_eq!(or.postings[8].frequency, 100);
+
+        assert!(intersect::<ManualZerocopy, Vec<u8>>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_intersect_skips_blocks() {
+        let dense: Vec<u64> = (0..300).collect();
+        let a = list(&dense, 1);
+        let b = list(&[150], 1);
+
+        let and = intersect::<ManualZerocopy, _>(&[&a, &b]).unwrap();
+        assert_eq!(doc_ids(&and), [150]);
+
+        // The dense list decodes its first block and the block holding 150,
+        // and skips the 49 blocks in between
+        assert_eq!(and.blocks_decoded, 3);
+        assert_eq!(and.blocks_skipped, 49);
+
+        // Decode-first codecs decode each block they reach exactly once
+        let serialize = |list: &[Vec<u8>]| -> Vec<Vec<u8>> {
+            list.iter()
+                .map(|bytes| {
+                    let block = manual_zerocopy::deserialize(bytes).unwrap();
+                    bincode::encode_to_vec(&block, bincode::config::standard()).unwrap()
+                })
+                .collect()
+        };
+        let and = intersect::<Bincode, _>(&[&serialize(&a), &serialize(&b)]).unwrap();
+        assert_eq!(doc_ids(&and), [150]);
+        assert_eq!(and.blocks_decoded, 52);
+        assert_eq!(and.blocks_skipped, 0);
+    }
+
+    fn check_codec<C: PostingsCodec, B: AsRef<[u8]>>(serialize: impl Fn(&Block) -> B) {
+        let lists = [
+            generate_postings(1, 20_000, 0.5),
+            generate_postings(2, 20_000, 0.1),
+        ];
+        let serialized: Vec<Vec<B>> = lists
+            .iter()
+            .map(|blocks| blocks.iter().map(&serialize).collect())
+            .collect();
+        let lists_ref: Vec<&[B]> = serialized.iter().map(Vec::as_slice).collect();
+
+        let reference: Vec<Vec<Vec<u8>>> = lists
+            .iter()
+            .map(|blocks| blocks.iter().map(manual_zerocopy::serialize).collect())
+            .collect();
+        let reference_ref: Vec<&[Vec<u8>]> = reference.iter().map(Vec::as_slice).collect();
+
+        let and = intersect::<C, B>(&lists_ref).unwrap();
+        let expected = intersect::<ManualZerocopy, _>(&reference_ref).unwrap();
+        assert!(!and.postings.is_empty());
+        assert_eq!(and.postings, expected.postings);
+
+        let or = union::<C, B>(&lists_ref).unwrap();
+        let expected = union::<ManualZerocopy, _>(&reference_ref).unwrap();
+        assert_eq!(or.postings, expected.postings);
+    }
+
+    #[test]
+    fn test_codecs_agree() {
+        check_codec::<Rkyv, _>(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap());
+        check_codec::<Bincode, _>(|block| {
+            bincode::encode_to_vec(block, bincode::config::standard()).unwrap()
+        });
+        check_codec::<Postcard, _>(|block| postcard::to_stdvec(block).unwrap());
+        check_codec::<MessagePack, _>(|block| rmp_serde::to_vec(block).unwrap());
+        check_codec::<Cbor, _>(|block| {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(block, &mut bytes).unwrap();
+            bytes
+        });
+        check_codec::<Bitcode, _>(bitcode::encode);
+        check_codec::<Borsh, _>(|block| borsh::to_vec(block).unwrap());
+        check_codec::<Speedy, _>(|block| speedy::Writable::write_to_vec(block).unwrap());
+        check_codec::<Capnp, _>(|block| {
+            let mut message = capnp::message::Builder::new_default();
+            block.to_capnp(&mut message);
+            capnp::serialize::write_message_to_words(&message)
+        });
+        check_codec::<Flatbuffers, _>(|block| {
+            let mut builder = flatbuffers::FlatBufferBuilder::new();
+            block.to_flatbuffers(&mut builder);
+            builder.finished_data().to_vec()
+        });
+        check_codec::<Protobuf, _>(|block| block.to_proto().encode_to_vec());
+        check_codec::<ManualZerocopyV2, _>(manual_zerocopy_v2::serialize);
+        check_codec::<ManualZerocopyV4, _>(manual_zerocopy_v4::serialize);
+        check_codec::<ManualBitpacked, _>(manual_bitpacked::serialize);
+        check_codec::<ManualEliasFano, _>(|block| manual_elias_fano::serialize(block).unwrap());
+        check_codec::<ManualDictionary, _>(manual_dictionary::serialize);
+        check_codec::<ManualRoaring, _>(manual_roaring::serialize);
+    }
+}
@@ -0,0 +1,326 @@
+//! Term filters combining a field mask condition with frequency bounds
+//!
+//! A [`Predicate`] checks a term's field mask against a [`MaskCondition`] and
+//! its frequency against an inclusive `[min_frequency, max_frequency]` range.
+//! [`Predicate::matches`] works on any term type implementing [`TermView`],
+//! which covers the term readers of every zero-copy format as well as
+//! [`FullTerm`], and only reads the frequency once the mask has matched. The
+//! Cap'n Proto term reader's mask getter can fail, so it is checked with
+//! [`Predicate::matches_mask`] and [`Predicate::matches_frequency`] instead.
+//!
+//! [`Predicate::evaluate_batch`] is the vectorized path for formats that
+//! expose their terms as a slice (v4, rkyv and [`FullTerm`]): it evaluates
+//! 64 terms at a time without branches, always reading the frequency, and
+//! returns the matches as a bitset the compiler can fill with SIMD compares.
+//!
+//! The set-at-a-time paths evaluate predicates too:
+//! [`manual_dictionary::BlockReader::evaluate_predicate`] checks the mask
+//! condition once per dictionary entry, and
+//! [`crate::manual_roaring::BlockReader::matching_predicate`] builds the matching
+//! positions from bitmap unions, intersections and complements.
+
+use crate::{
+    manual_bitpacked, manual_dictionary, manual_elias_fano, manual_zerocopy, manual_zerocopy_v2,
+    manual_zerocopy_v4, ArchivedFullTerm, FullTerm,
+};
+
+/// Condition on a term's field mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskCondition {
+    /// Every mask matches
+    Always,
+    /// At least one bit of the query is set (`mask & query != 0`)
+    Any(u128),
+    /// Every bit of the query is set (`mask & query == query`)
+    All(u128),
+    /// No bit of the query is set (`mask & query == 0`)
+    NoneOf(u128),
+}
+
+impl MaskCondition {
+    #[inline(always)]
+    pub fn matches(&self, field_mask: u128) -> bool {
+        match *self {
+            MaskCondition::Always => true,
+            MaskCondition::Any(query_mask) => field_mask & query_mask != 0,
+            MaskCondition::All(query_mask) => field_mask & query_mask == query_mask,
+            MaskCondition::NoneOf(query_mask) => field_mask & query_mask == 0,
+        }
+    }
+
+    /// `(query, target, negate)` such that the condition holds exactly when
+    /// `(mask & query == target) != negate`, for branch-free evaluation
+    fn branch_free(&self) -> (u128, u128, bool) {
+        match *self {
+            MaskCondition::Always => (0, 0, false),
+            MaskCondition::Any(query_mask) => (query_mask, 0, true),
+            MaskCondition::All(query_mask) => (query_mask, query_mask, false),
+            MaskCondition::NoneOf(query_mask) => (query_mask, 0, false),
+        }
+    }
+}
+
+/// Filter on a term's field mask and frequency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Predicate {
+    pub mask: MaskCondition,
+    pub min_frequency: u64,
+    pub max_frequency: u64,
+}
+
+impl Predicate {
+    /// Predicate that matches every term
+    pub const ALWAYS: Predicate = Predicate {
+        mask: MaskCondition::Always,
+        min_frequency: 0,
+        max_frequency: u64::MAX,
+    };
+
+    /// Terms with at least one bit of `query_mask`
+    pub fn any(query_mask: u128) -> Self {
+        Predicate {
+            mask: MaskCondition::Any(query_mask),
+            ..Self::ALWAYS
+        }
+    }
+
+    /// Terms with every bit of `query_mask`
+    pub fn all(query_mask: u128) -> Self {
+        Predicate {
+            mask: MaskCondition::All(query_mask),
+            ..Self::ALWAYS
+        }
+    }
+
+    /// Terms with no bit of `query_mask`
+    pub fn none(query_mask: u128) -> Self {
+        Predicate {
+            mask: MaskCondition::NoneOf(query_mask),
+            ..Self::ALWAYS
+        }
+    }
+
+    /// Additionally require a frequency of at least `min_frequency`
+    pub fn with_min_frequency(self, min_frequency: u64) -> Self {
+        Predicate {
+            min_frequency,
+            ..self
+        }
+    }
+
+    /// Additionally require a frequency of at most `max_frequency`
+    pub fn with_max_frequency(self, max_frequency: u64) -> Self {
+        Predicate {
+            max_frequency,
+            ..self
+        }
+    }
+
+    #[inline(always)]
+    pub fn matches_mask(&self, field_mask: u128) -> bool {
+        self.mask.matches(field_mask)
+    }
+
+    #[inline(always)]
+    pub fn matches_frequency(&self, frequency: u64) -> bool {
+        self.min_frequency <= frequency && frequency <= self.max_frequency
+    }
+
+    /// Whether the frequency bounds exclude anything, so callers can avoid
+    /// reading frequencies when they don't
+    #[inline(always)]
+    pub fn has_frequency_bounds(&self) -> bool {
+        self.min_frequency > 0 || self.max_frequency < u64::MAX
+    }
+
+    /// Evaluate against a term, reading the frequency only if the mask matches
+    #[inline(always)]
+    pub fn matches<T: TermView>(&self, term: &T) -> bool {
+        self.matches_mask(term.field_mask()) && self.matches_frequency(term.frequency())
+    }
+
+    /// Evaluate against a slice of terms, 64 at a time without branches
+    ///
+    /// Clears `matches` and sets bit `i % 64` of word `i / 64` for every
+    /// matching term `i`.
+    pub fn evaluate_batch<T: TermView>(&self, terms: &[T], matches: &mut Vec<u64>) {
+        let (query_mask, target, negate) = self.mask.branch_free();
+
+        matches.clear();
+        matches.extend(terms.chunks(64).map(|chunk| {
+            chunk.iter().enumerate().fold(0u64, |word, (i, term)| {
+                let frequency = term.frequency();
+                let hit = ((term.field_mask() & query_mask == target) != negate)
+                    & (self.min_frequency <= frequency)
+                    & (frequency <= self.max_frequency);
+                word | (hit as u64) << i
+            })
+        }));
+    }
+}
+
+/// Field access shared by the term types of every format
+pub trait TermView {
+    fn field_mask(&self) -> u128;
+    fn frequency(&self) -> u64;
+}
+
+impl TermView for FullTerm {
+    #[inline(always)]
+    fn field_mask(&self) -> u128 {
+        self.field_mask
+    }
+
+    #[inline(always)]
+    fn frequency(&self) -> u64 {
+        self.frequency
+    }
+}
+
+impl TermView for ArchivedFullTerm {
+    #[inline(always)]
+    fn field_mask(&self) -> u128 {
+        self.field_mask.to_native()
+    }
+
+    #[inline(always)]
+    fn frequency(&self) -> u64 {
+        self.frequency.to_native()
+    }
+}
+
+// Term readers that already expose `field_mask()` and `frequency()`
+macro_rules! impl_term_view {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl TermView for $ty {
+                #[inline(always)]
+                fn field_mask(&self) -> u128 {
+                    <$ty>::field_mask(self)
+                }
+
+                #[inline(always)]
+                fn frequency(&self) -> u64 {
+                    <$ty>::frequency(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_term_view!(
+    manual_zerocopy::TermReader<'_>,
+    manual_zerocopy_v2::ArchivedFullTerm<'_>,
+    manual_zerocopy_v4::TermLe,
+    manual_bitpacked::TermReader<'_, '_>,
+    manual_elias_fano::TermReader<'_, '_>,
+    manual_dictionary::TermReader<'_, '_>,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{manual_roaring, Block};
+
+    fn term(field_mask: u128, frequency: u64) -> FullTerm {
+        FullTerm {
+            doc_id: 0,
+            field_mask,
+            frequency,
+        }
+    }
+
+    #[test]
+    fn test_conditions() {
+        let term = term(0b0110, 50);
+
+        assert!(Predicate::ALWAYS.matches(&term));
+        assert!(Predicate::any(0b0011).matches(&term));
+        assert!(!Predicate::any(0b1001).matches(&term));
+        assert!(Predicate::all(0b0110).matches(&term));
+        assert!(!Predicate::all(0b0111).matches(&term));
+        assert!(Predicate::none(0b1001).matches(&term));
+        assert!(!Predicate::none(0b0010).matches(&term));
+
+        assert!(Predicate::any(0b0010).with_min_frequency(50).matches(&term));
+        assert!(!Predicate::any(0b0010).with_min_frequency(51).matches(&term));
+        assert!(!Predicate::ALWAYS.with_max_frequency(49).matches(&term));
+        assert!(!Predicate::ALWAYS.has_frequency_bounds());
+    }
+
+    #[test]
+    fn test_set_paths_match_scan() {
+        let block = Block {
+            full_terms: vec![
+                term(0b0001, 10),
+                term(0b0011, 20),
+                term(0b0110, 30),
+                term(0, 40),
+                term(0b0111, 50),
+            ],
+        };
+
+        let dictionary_bytes = manual_dictionary::serialize(&block);
+        let dictionary = manual_dictionary::BlockReader::new(&dictionary_bytes).unwrap();
+        let roaring_bytes = manual_roaring::serialize(&block);
+        let roaring = manual_roaring::BlockReader::new(&roaring_bytes).unwrap();
+        let mut hit_set = manual_dictionary::HitSet::new();
+
+        for predicate in [
+            Predicate::ALWAYS,
+            Predicate::any(0b0100),
+            Predicate::all(0b0011),
+            Predicate::all(0b1000),
+            Predicate::all(0),
+            Predicate::none(0b0001),
+            Predicate::none(0b0001).with_min_frequency(35),
+            Predicate::any(0b0011).with_max_frequency(20),
+        ] {
+            let expected: Vec<u32> = (0..block.full_terms.len() as u32)
+                .filter(|&i| predicate.matches(&block.full_terms[i as usize]))
+                .collect();
+
+            dictionary.evaluate_predicate(&predicate, &mut hit_set);
+            let actual: Vec<u32> = (0..dictionary.len())
+                .filter(|&i| {
+                    hit_set.contains(dictionary.mask_index(i))
+                        && predicate.matches_frequency(dictionary.frequency(i))
+                })
+                .map(|i| i as u32)
+                .collect();
+            assert_eq!(actual, expected, "dictionary {predicate:?}");
+
+            let actual: Vec<u32> = roaring
+                .matching_predicate(&predicate)
+                .unwrap()
+                .iter()
+                .collect();
+            assert_eq!(actual, expected, "roaring {predicate:?}");
+        }
+    }
+
+    #[test]
+    fn test_batch_matches_scan() {
+        let block = &crate::generate_test_data()[0];
+        let bytes = manual_zerocopy_v4::serialize(block);
+        let reader = manual_zerocopy_v4::BlockReader::new(&bytes).unwrap();
+        let mut matches = Vec::new();
+
+        for predicate in [
+            Predicate::ALWAYS,
+            Predicate::any(0b1010),
+            Predicate::all(0b11),
+            Predicate::none(0b11),
+            Predicate::any(0b1010).with_min_frequency(500),
+            Predicate::ALWAYS.with_max_frequency(100),
+        ] {
+            predicate.evaluate_batch(reader.terms(), &mut matches);
+            assert_eq!(matches.len(), reader.len().div_ceil(64));
+
+            for (i, term) in reader.iter().enumerate() {
+                let hit = matches[i / 64] & (1 << (i % 64)) != 0;
+                assert_eq!(hit, predicate.matches(term), "{predicate:?} term {i}");
+            }
+        }
+    }
+}
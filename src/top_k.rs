@@ -0,0 +1,306 @@
+//! Top-k query by frequency over a stream of blocks
+//!
+//! [`TopK`] keeps the `k` best postings seen so far in a min-heap, so a
+//! candidate only has to beat the current minimum ([`TopK::threshold`]) to get
+//! in. The query functions run on top of the zero-copy readers and use that
+//! threshold to drop terms after reading just the mask and frequency, without
+//! touching the doc id. Decode-first codecs can feed a [`TopK`] through
+//! [`TopK::push_block`] after decoding each block.
+//!
+//! Ties on frequency go to the lower doc id, so results are deterministic.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use capnp::message::ReaderOptions;
+
+use crate::postings::Posting;
+use crate::predicate::Predicate;
+use crate::{block_capnp, capnp_view, manual_zerocopy, manual_zerocopy_v4, ArchivedBlock, Block};
+
+/// Heap entry ordered by frequency, then by lower doc id
+#[derive(PartialEq, Eq)]
+struct Candidate(Posting);
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .frequency
+            .cmp(&other.0.frequency)
+            .then_with(|| other.0.doc_id.cmp(&self.0.doc_id))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Collector for the `k` postings with the highest frequency
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<Candidate>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        TopK {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    /// Lowest frequency that can still enter the result, once `k` postings
+    /// have been collected
+    ///
+    /// A frequency equal to the threshold only gets in with a lower doc id.
+    #[inline]
+    pub fn threshold(&self) -> Option<u64> {
+        if self.heap.len() < self.k {
+            return None;
+        }
+
+        self.heap
+            .peek()
+            .map(|Reverse(candidate)| candidate.0.frequency)
+    }
+
+    /// Whether a posting with `frequency` could enter the result
+    #[inline(always)]
+    pub fn accepts(&self, frequency: u64) -> bool {
+        self.k > 0
+            && self
+                .threshold()
+                .is_none_or(|threshold| frequency >= threshold)
+    }
+
+    /// Offer a posting
+    #[inline]
+    pub fn push(&mut self, doc_id: u64, frequency: u64) {
+        if !self.accepts(frequency) {
+            return;
+        }
+
+        self.heap
+            .push(Reverse(Candidate(Posting { doc_id, frequency })));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// Offer every term of a decoded block that matches `predicate`
+    pub fn push_block(&mut self, block: &Block, predicate: &Predicate) {
+        for term in &block.full_terms {
+            if predicate.matches(term) {
+                self.push(term.doc_id, term.frequency);
+            }
+        }
+    }
+
+    /// Collected postings, highest frequency first
+    pub fn into_sorted_vec(self) -> Vec<Posting> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(candidate)| candidate.0)
+            .collect()
+    }
+}
+
+/// Top-k over [`manual_zerocopy`](mod@manual_zerocopy) blocks
+pub fn manual_zerocopy<B: AsRef<[u8]>>(
+    blocks: &[B],
+    k: usize,
+    predicate: &Predicate,
+) -> Result<Vec<Posting>, &'static str> {
+    let mut top_k = TopK::new(k);
+
+    for bytes in blocks {
+        for term in manual_zerocopy::BlockReader::new(bytes.as_ref())?.iter() {
+            if predicate.matches_mask(term.field_mask()) {
+                let frequency = term.frequency();
+                if predicate.matches_frequency(frequency) && top_k.accepts(frequency) {
+                    top_k.push(term.doc_id(), frequency);
+                }
+            }
+        }
+    }
+
+    Ok(top_k.into_sorted_vec())
+}
+
+/// Top-k over [`manual_zerocopy_v4`](mod@manual_zerocopy_v4) blocks
+pub fn manual_zerocopy_v4<B: AsRef<[u8]>>(
+    blocks: &[B],
+    k: usize,
+    predicate: &Predicate,
+) -> Result<Vec<Posting>, &'static str> {
+    let mut top_k = TopK::new(k);
+
+    for bytes in blocks {
+        for term in manual_zerocopy_v4::BlockReader::new(bytes.as_ref())?.iter() {
+            if predicate.matches_mask(term.field_mask()) {
+                let frequency = term.frequency();
+                if predicate.matches_frequency(frequency) && top_k.accepts(frequency) {
+                    top_k.push(term.doc_id(), frequency);
+                }
+            }
+        }
+    }
+
+    Ok(top_k.into_sorted_vec())
+}
+
+/// Top-k over rkyv blocks, read in place through the archived block
+pub fn rkyv<B: AsRef<[u8]>>(
+    blocks: &[B],
+    k: usize,
+    predicate: &Predicate,
+) -> Result<Vec<Posting>, &'static str> {
+    let mut top_k = TopK::new(k);
+
+    for bytes in blocks {
+        let block = rkyv::access::<ArchivedBlock, rkyv::rancor::Error>(bytes.as_ref())
+            .map_err(|_| "Invalid rkyv block")?;
+
+        for term in block.full_terms.iter() {
+            if predicate.matches_mask(term.field_mask.to_native()) {
+                let frequency = term.frequency.to_native();
+                if predicate.matches_frequency(frequency) && top_k.accepts(frequency) {
+                    top_k.push(term.doc_id.to_native(), frequency);
+                }
+            }
+        }
+    }
+
+    Ok(top_k.into_sorted_vec())
+}
+
+/// Top-k over Cap'n Proto blocks, read in place through [`capnp_view`]
+pub fn capnp<B: AsRef<[u8]>>(
+    blocks: &[B],
+    k: usize,
+    predicate: &Predicate,
+) -> Result<Vec<Posting>, &'static str> {
+    let mut top_k = TopK::new(k);
+
+    for bytes in blocks {
+        let message = capnp::serialize::read_message_from_flat_slice(
+            &mut bytes.as_ref(),
+            ReaderOptions::new(),
+        )
+        .map_err(|_| "Invalid capnp message")?;
+        let root = message
+            .get_root::<block_capnp::block::Reader>()
+            .map_err(|_| "Invalid capnp block")?;
+
+        for term in capnp_view::BlockReader::new(root)
+            .map_err(|_| "Invalid capnp block")?
+            .iter()
+        {
+            let field_mask = term.field_mask().map_err(|_| "Invalid capnp field mask")?;
+            if predicate.matches_mask(field_mask) {
+                let frequency = term.frequency();
+                if predicate.matches_frequency(frequency) && top_k.accepts(frequency) {
+                    top_k.push(term.doc_id(), frequency);
+                }
+            }
+        }
+    }
+
+    Ok(top_k.into_sorted_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_test_data_with_distinct_masks;
+
+    #[test]
+    fn test_top_k_order() {
+        let mut top_k = TopK::new(3);
+        for (doc_id, frequency) in [(1, 5), (2, 9), (3, 1), (4, 9), (5, 7), (6, 5)] {
+            top_k.push(doc_id, frequency);
+        }
+
+        assert_eq!(top_k.threshold(), Some(7));
+        assert_eq!(
+            top_k.into_sorted_vec(),
+            [
+                Posting {
+                    doc_id: 2,
+                    frequency: 9
+                },
+                Posting {
+                    doc_id: 4,
+                    frequency: 9
+                },
+                Posting {
+                    doc_id: 5,
+                    frequency: 7
+                },
+            ]
+        );
+
+        let mut empty = TopK::new(0);
+        empty.push(1, 100);
+        assert!(empty.into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn test_readers_match_decoded() {
+        let test_data: Vec<Block> = generate_test_data_with_distinct_masks(16)
+            .into_iter()
+            .take(200)
+            .collect();
+        let query_mask = test_data[0].full_terms[0].field_mask;
+
+        for predicate in [
+            Predicate::ALWAYS,
+            Predicate::any(query_mask),
+            Predicate::none(query_mask).with_max_frequency(500),
+        ] {
+            let mut expected = TopK::new(10);
+            for block in &test_data {
+                expected.push_block(block, &predicate);
+            }
+            let expected = expected.into_sorted_vec();
+            assert_eq!(expected.len(), 10);
+
+            let manual_blocks: Vec<_> = test_data
+                .iter()
+                .map(crate::manual_zerocopy::serialize)
+                .collect();
+            assert_eq!(
+                manual_zerocopy(&manual_blocks, 10, &predicate).unwrap(),
+                expected
+            );
+
+            let manual_v4_blocks: Vec<_> = test_data
+                .iter()
+                .map(crate::manual_zerocopy_v4::serialize)
+                .collect();
+            assert_eq!(
+                manual_zerocopy_v4(&manual_v4_blocks, 10, &predicate).unwrap(),
+                expected
+            );
+
+            let rkyv_blocks: Vec<_> = test_data
+                .iter()
+                .map(|block| rkyv::to_bytes::<rkyv::rancor::Error>(block).unwrap())
+                .collect();
+            assert_eq!(rkyv(&rkyv_blocks, 10, &predicate).unwrap(), expected);
+
+            let capnp_blocks: Vec<_> = test_data
+                .iter()
+                .map(|block| {
+                    let mut message = capnp::message::Builder::new_default();
+                    block.to_capnp(&mut message);
+                    capnp::serialize::write_message_to_words(&message)
+                })
+                .collect();
+            assert_eq!(capnp(&capnp_blocks, 10, &predicate).unwrap(), expected);
+        }
+    }
+}